@@ -0,0 +1,80 @@
+#![cfg(feature = "async")]
+use futures::task::LocalSpawnExt;
+use futures::StreamExt;
+use thread_groups::{Result, ThreadGroup};
+
+#[test]
+fn test_join_all() -> Result<()> {
+    let mut threads = ThreadGroup::<u64>::with_id(format!("{}:{}", module_path!(), line!()));
+    for number in [3, 1, 2] {
+        threads.spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(number * 150));
+            if number == 2 {
+                panic!("synthetic error at number {}", number)
+            }
+            number
+        })?;
+    }
+    let data = futures::executor::block_on(threads.join_all());
+    assert_eq!(data.len(), 3);
+    assert_eq!(data[0], Ok(1));
+    assert!(data[1].is_err());
+    assert_eq!(data[2], Ok(3));
+    assert_eq!(threads.errors().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_stream() -> Result<()> {
+    let mut threads = ThreadGroup::<u64>::with_id(format!("{}:{}", module_path!(), line!()));
+    for number in [3, 1, 2] {
+        threads.spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(number * 150));
+            number
+        })?;
+    }
+    let mut pool = futures::executor::LocalPool::new();
+    let data = pool.run_until(async {
+        let mut data = Vec::new();
+        while let Some(result) = threads.next().await {
+            data.push(result);
+        }
+        data
+    });
+    assert_eq!(data.into_iter().collect::<Result<Vec<u64>>>()?, vec![1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+fn test_join_all_waits_for_children_without_blocking() -> Result<()> {
+    let mut threads = ThreadGroup::<u64>::with_id(format!("{}:{}", module_path!(), line!()));
+    let mut child = threads.child("child");
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    child.spawn(move || {
+        receiver.recv().ok();
+    })?;
+    threads.spawn(|| 1)?;
+    let mut pool = futures::executor::LocalPool::new();
+    let joined = pool
+        .spawner()
+        .spawn_local_with_handle(async move {
+            let data = threads.join_all().await;
+            (data, threads)
+        })
+        .expect("spawn future");
+    pool.run_until_stalled();
+    sender.send(()).expect("child thread waiting");
+    let (data, _threads) = pool.run_until(joined);
+    assert_eq!(data, vec![Ok(1)]);
+    assert_eq!(child.results(), vec![Ok(())]);
+    Ok(())
+}
+
+#[test]
+fn test_stream_releases_start_gate() -> Result<()> {
+    let mut threads = ThreadGroup::<u64>::with_start_gate();
+    threads.spawn(|| 1)?;
+    let data = futures::executor::block_on(threads.join_all());
+    assert_eq!(data, vec![Ok(1)]);
+    Ok(())
+}
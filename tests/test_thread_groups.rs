@@ -1,4 +1,8 @@
-use thread_groups::{Error, Result, ThreadGroup};
+#![allow(clippy::manual_filter_map)]
+use thread_groups::{
+    ContextPropagator, DropPolicy, Error, Result, RetryPolicy, ThreadGroup, ThreadGroupBuilder,
+    ThreadOptions, ThreadPoolGroup, TryThreadGroup,
+};
 
 #[test]
 fn test_join() -> Result<()> {
@@ -74,3 +78,987 @@ fn test_all_ok() -> Result<()> {
     assert!(threads.errors().is_empty());
     Ok(())
 }
+
+#[test]
+fn test_join_next_completed() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<u32>::with_id(id.clone());
+    threads.spawn(|| {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        401
+    })?;
+    threads.spawn(|| 402)?;
+    let (task, data) = threads.join_next_completed()?;
+    assert_eq!(task.name(), format!("{}:2", id));
+    assert_eq!(task.index(), 2);
+    assert_eq!(data, 402);
+    assert_eq!(threads.join_any()?, 401);
+    assert!(threads.join_any().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_results_in_completion_order() -> Result<()> {
+    let mut threads = ThreadGroup::<u64>::with_id(format!("{}:{}", module_path!(), line!()));
+    for number in [3, 1, 2] {
+        threads.spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(number * 150));
+            number
+        })?;
+    }
+    let data = threads
+        .results_in_completion_order()
+        .into_iter()
+        .collect::<Result<Vec<u64>>>()?;
+    assert_eq!(data, vec![1, 2, 3]);
+    assert!(threads.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_join_timeout() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<u32>::with_id(id.clone());
+    threads.spawn(|| {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        401
+    })?;
+    let error = threads.join_timeout(std::time::Duration::from_millis(10)).unwrap_err();
+    assert_eq!(error, Error::Timeout { group: id.clone(), running: vec![format!("{}:1", id)] });
+    assert_eq!(threads.join_timeout(std::time::Duration::from_secs(10))?, 401);
+    Ok(())
+}
+
+#[test]
+fn test_all_ok_timeout() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<u32>::with_id(id.clone());
+    threads.spawn(|| 401)?;
+    threads.spawn(|| {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        402
+    })?;
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(50);
+    let error = threads.all_ok_deadline(deadline).unwrap_err();
+    assert_eq!(error.variant(), "Timeout");
+    assert_eq!(error, Error::Timeout { group: id.clone(), running: vec![format!("{}:2", id)] });
+    assert_eq!(threads.results_timeout(std::time::Duration::from_secs(10))?.len(), 2);
+    assert!(threads.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_timeout_on_empty_group() -> Result<()> {
+    let mut threads = ThreadGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()));
+    let timeout = std::time::Duration::from_millis(10);
+    assert_eq!(threads.results_timeout(timeout)?, vec![]);
+    assert_eq!(threads.as_far_as_ok_timeout(timeout)?, vec![]);
+    assert_eq!(threads.all_ok_timeout(timeout)?, vec![]);
+    assert_eq!(threads.join_timeout(timeout).unwrap_err().variant(), "ThreadGroupError");
+    Ok(())
+}
+
+#[test]
+fn test_panic_payloads() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<u32>::with_id(id.clone());
+    threads.spawn(|| panic!("synthetic error at number {}", 401))?;
+    threads.spawn(|| std::panic::panic_any(402u32))?;
+
+    let error = threads.join().unwrap_err();
+    assert!(error.to_string().ends_with(": synthetic error at number 401"));
+    assert_eq!(
+        threads.join().unwrap_err(),
+        Error::ThreadJoinError { thread: format!("{}:2", id), index: 2, message: None }
+    );
+
+    let key = format!("{}:{}:1", std::process::id(), id);
+    let payload = threads.take_panic(&key).expect("panic payload");
+    assert_eq!(thread_groups::panic_message(&*payload).unwrap(), "synthetic error at number 401");
+
+    let payloads = threads.take_panics();
+    assert_eq!(payloads.len(), 1);
+    let payload = payloads.into_values().next().unwrap();
+    assert_eq!(payload.downcast_ref::<u32>(), Some(&402));
+    assert_eq!(threads.errors().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_scope() -> Result<()> {
+    let numbers = (401..409).collect::<Vec<u32>>();
+    let data = thread_groups::scope_with_id(format!("{}:{}", module_path!(), line!()), |threads| {
+        for number in &numbers {
+            threads.spawn(move || {
+                if number % 2 == 0 && *number < 407 {
+                    panic!("synthetic error at number {}", number)
+                }
+                *number
+            })?;
+        }
+        let data = threads.as_far_as_ok();
+        assert_eq!(threads.errors().len(), 3);
+        Ok::<Vec<u32>, Error>(data)
+    })?;
+    assert_eq!(data, vec![401, 403, 405, 407, 408]);
+    Ok(())
+}
+
+#[test]
+fn test_scope_joins_leftover_threads() {
+    let mut numbers = Vec::<u32>::new();
+    thread_groups::scope(|threads| {
+        threads.spawn(|| panic!("synthetic error"))?;
+        threads.spawn(|| numbers.len())
+    })
+    .unwrap();
+    numbers.push(401);
+    assert_eq!(numbers, vec![401]);
+}
+
+#[test]
+fn test_thread_pool_group() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadPoolGroup::<u32>::with_id(id.clone(), 3);
+    let workers = std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+    for number in 1..=1000 {
+        let workers = workers.clone();
+        threads.spawn(move || {
+            workers.lock().unwrap().insert(std::thread::current().id());
+            if number % 2 == 0 && number < 7 {
+                panic!("synthetic error at number {}", number)
+            }
+            number
+        })?;
+    }
+    let data = threads.as_far_as_ok();
+    assert_eq!(data.len(), 997);
+    assert_eq!(&data[..4], &[1, 3, 5, 7]);
+    assert!(workers.lock().unwrap().len() <= 3);
+    assert_eq!(threads.errors().len(), 3);
+    assert!(threads.errors().contains_key(&format!("{}:{}:2", std::process::id(), id)));
+    Ok(())
+}
+
+#[test]
+fn test_thread_pool_group_all_ok() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadPoolGroup::<String>::with_id(id, 2);
+    for number in 401..409 {
+        threads.spawn(move || format!("{}", number))?;
+    }
+    let data = threads.all_ok()?;
+    assert_eq!(data, vec!["401", "402", "403", "404", "405", "406", "407", "408"]);
+    assert!(threads.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_thread_pool_group_drop_discards_queued() -> Result<()> {
+    let mut threads = ThreadPoolGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()), 1);
+    let ran = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    for _ in 0..3 {
+        let ran = ran.clone();
+        threads.spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            ran.fetch_add(1, std::sync::atomic::Ordering::SeqCst) as u32
+        })?;
+    }
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    drop(threads);
+    assert_eq!(ran.load(std::sync::atomic::Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[test]
+fn test_cancel_on_failure() -> Result<()> {
+    let mut threads = ThreadGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()));
+    threads.set_cancel_on_failure(true);
+    threads.spawn(|| panic!("synthetic error"))?;
+    for number in 402..409 {
+        threads.spawn_cancellable(move |token| {
+            while !token.is_cancelled() {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            number
+        })?;
+    }
+    assert!(threads.all_ok().is_err());
+    assert!(threads.is_cancelled());
+    assert_eq!(threads.as_far_as_ok(), vec![402, 403, 404, 405, 406, 407, 408]);
+    assert_eq!(threads.errors().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_cancel() -> Result<()> {
+    let mut threads = ThreadGroup::<bool>::with_id(format!("{}:{}", module_path!(), line!()));
+    let token = threads.token();
+    threads.spawn_cancellable(|token| {
+        while !token.is_cancelled() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        token.is_cancelled()
+    })?;
+    assert!(!token.is_cancelled());
+    threads.cancel();
+    assert!(token.is_cancelled());
+    assert!(threads.join()?);
+    Ok(())
+}
+
+#[test]
+fn test_all_ok_fail_fast() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<u32>::with_id(id.clone());
+    threads.spawn_cancellable(|token| {
+        while !token.is_cancelled() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        401
+    })?;
+    threads.spawn(|| panic!("synthetic error at number {}", 402))?;
+    let error = threads.all_ok_fail_fast().unwrap_err();
+    match &error {
+        Error::Aborted { cause, running } => {
+            assert_eq!(
+                cause.as_ref(),
+                &Error::ThreadJoinError {
+                    thread: format!("{}:2", id),
+                    index: 2,
+                    message: Some("synthetic error at number 402".to_string()),
+                }
+            );
+            assert_eq!(running, &vec![format!("{}:1", id)]);
+        },
+        _ => panic!("unexpected error {}", error),
+    }
+    assert!(std::error::Error::source(&error).is_some());
+    threads.cancel();
+    assert_eq!(threads.all_ok_fail_fast()?, vec![401]);
+    Ok(())
+}
+
+#[test]
+fn test_results_by_id() -> Result<()> {
+    let mut threads = ThreadGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()));
+    let mut inputs = std::collections::BTreeMap::new();
+    for number in 401..409 {
+        let task = threads.spawn(move || {
+            if number % 2 == 0 && number < 407 {
+                panic!("synthetic error at number {}", number)
+            }
+            number
+        })?;
+        inputs.insert(task, number);
+    }
+    let data = threads.results_by_id();
+    assert_eq!(data.len(), 8);
+    for (task, result) in data {
+        match result {
+            Ok(number) => assert_eq!(inputs[&task], number),
+            Err(_) => assert!(inputs[&task] % 2 == 0 && inputs[&task] < 407),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_join_id() -> Result<()> {
+    let mut threads = ThreadGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()));
+    let first = threads.spawn(|| 401)?;
+    let second = threads.spawn(|| 402)?;
+    assert_eq!(threads.join_id(&second)?, 402);
+    assert!(threads.join_id(&second).is_err());
+    assert_eq!(threads.join_id(&first)?, 401);
+    Ok(())
+}
+
+#[test]
+fn test_thread_names_are_never_reused() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<u32>::with_id(id.clone());
+    threads.spawn(|| panic!("synthetic error at number {}", 401))?;
+    threads.spawn(|| panic!("synthetic error at number {}", 402))?;
+    assert!(threads.join().is_err());
+    let task = threads.spawn(|| panic!("synthetic error at number {}", 403))?;
+    assert_eq!(task.name(), format!("{}:3", id));
+    assert_eq!(threads.results().len(), 2);
+
+    let errors = threads.errors();
+    assert_eq!(errors.len(), 3);
+    for index in 1..=3 {
+        assert!(errors.contains_key(&format!("{}:{}:{}", std::process::id(), id, index)));
+    }
+    assert_eq!(threads.spawned(), 3);
+    assert_eq!(threads.joined(), 3);
+    assert_eq!(threads.failed(), 3);
+    assert_eq!(threads.live(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_error_display() {
+    let error = Error::ThreadJoinError {
+        thread: "group:1".to_string(),
+        index: 1,
+        message: Some("synthetic error".to_string()),
+    };
+    assert_eq!(error.to_string(), "ThreadJoinError: joining thread group:1: synthetic error");
+    assert!(std::error::Error::source(&error).is_none());
+
+    let error = Error::ThreadSpawnError {
+        name: "group:2".to_string(),
+        source: std::sync::Arc::new(std::io::Error::from(std::io::ErrorKind::OutOfMemory)),
+    };
+    assert_eq!(error.variant(), "ThreadSpawnError");
+    assert!(error.to_string().starts_with("ThreadSpawnError: spawning thread group:2: "));
+    assert!(std::error::Error::source(&error).is_some());
+}
+
+fn recurse(depth: usize) -> usize {
+    let frame = std::hint::black_box([1u8; 1024]);
+    if depth == 0 {
+        return frame[0] as usize;
+    }
+    frame[depth % 1024] as usize + recurse(depth - 1)
+}
+
+#[test]
+fn test_builder() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroupBuilder::new()
+        .id(id.clone())
+        .name_template("worker-{index}@{group}")
+        .stack_size(64 * 1024 * 1024)
+        .build::<usize>();
+    let task = threads.spawn(|| recurse(20_000))?;
+    assert_eq!(task.name(), format!("worker-1@{}", id));
+    assert_eq!(threads.join()?, 20_001);
+    Ok(())
+}
+
+#[test]
+fn test_spawn_with() -> Result<()> {
+    let mut threads = ThreadGroup::<String>::with_id(format!("{}:{}", module_path!(), line!()));
+    let options = ThreadOptions::new().name("parser").stack_size(64 * 1024 * 1024);
+    let task = threads.spawn_with(options, || {
+        recurse(20_000);
+        std::thread::current().name().unwrap().to_string()
+    })?;
+    assert_eq!(task.name(), "parser");
+    assert_eq!(task.index(), 1);
+    assert_eq!(threads.join()?, "parser");
+    Ok(())
+}
+
+#[test]
+fn test_drain() -> Result<()> {
+    let mut threads = ThreadGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()));
+    for number in 401..409 {
+        threads.spawn(move || number)?;
+    }
+    let found = threads.drain().map(|result| result.unwrap()).find(|number| number % 4 == 0);
+    assert_eq!(found, Some(404));
+    assert_eq!(threads.live(), 4);
+    assert_eq!(threads.drain().len(), 4);
+    let data = (&mut threads).into_iter().collect::<Result<Vec<u32>>>()?;
+    assert_eq!(data, vec![405, 406, 407, 408]);
+    Ok(())
+}
+
+#[test]
+fn test_from_iterator() -> Result<()> {
+    let threads = (401..409)
+        .map(|number| {
+            move || {
+                if number % 2 == 0 && number < 407 {
+                    panic!("synthetic error at number {}", number)
+                }
+                number
+            }
+        })
+        .collect::<ThreadGroup<u32>>();
+    let data = threads.into_iter().filter_map(|result| result.ok()).collect::<Vec<u32>>();
+    assert_eq!(data, vec![401, 403, 405, 407, 408]);
+    Ok(())
+}
+
+#[test]
+fn test_map() -> Result<()> {
+    let mut threads = ThreadGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()));
+    let data = threads.map(401..409, |number| {
+        if number % 2 == 0 && number < 407 {
+            panic!("synthetic error at number {}", number)
+        }
+        number
+    });
+    let ok_data = data.iter().filter_map(|result| result.clone().ok()).collect::<Vec<u32>>();
+    assert_eq!(ok_data, vec![401, 403, 405, 407, 408]);
+    assert!(data[1].is_err());
+    assert_eq!(threads.errors().len(), 3);
+
+    let data = threads.try_map(vec![3u64, 1, 2], |number| {
+        std::thread::sleep(std::time::Duration::from_millis(number * 50));
+        number as u32
+    })?;
+    assert_eq!(data, vec![3, 1, 2]);
+    Ok(())
+}
+
+#[test]
+fn test_map_chunked() -> Result<()> {
+    let mut threads = ThreadGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()));
+    let events = Events::default();
+    threads.on_start(record(&events, "start"));
+    let data = threads.map_chunked(1..=10, 3, |number| {
+        if number == 9 {
+            panic!("synthetic error at number {}", number)
+        }
+        number * 2
+    });
+    assert_eq!(data.len(), 10);
+    let ok_data = data.iter().filter_map(|result| result.clone().ok()).collect::<Vec<u32>>();
+    assert_eq!(ok_data, vec![2, 4, 6, 8, 10, 12, 14, 16]);
+    assert!(data[8].is_err() && data[9].is_err());
+    assert_eq!(threads.errors().len(), 1);
+    assert_eq!(threads.failed(), 1);
+    assert_eq!(events.lock().unwrap().len(), 3);
+    assert_eq!(threads.stats().tasks.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_try_thread_group() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = TryThreadGroup::<u32, std::num::ParseIntError>::with_id(id.clone());
+    for input in ["401", "x402", "403"] {
+        threads.spawn(move || input.parse::<u32>())?;
+    }
+    threads.spawn(|| panic!("synthetic error at number {}", 404))?;
+
+    let data = threads.results();
+    assert_eq!(data[0], Ok(401));
+    assert_eq!(data[2], Ok(403));
+    assert_eq!(data[3].clone().unwrap_err().variant(), "ThreadJoinError");
+    let error = data[1].clone().unwrap_err();
+    match &error {
+        Error::TaskError { thread, index, source } => {
+            assert_eq!(thread, &format!("{}:2", id));
+            assert_eq!(*index, 2);
+            assert!(source.downcast_ref::<std::num::ParseIntError>().is_some());
+        },
+        _ => panic!("unexpected error {}", error),
+    }
+    assert!(std::error::Error::source(&error).is_some());
+    assert_eq!(threads.errors().len(), 2);
+    assert_eq!(threads.failed(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_try_thread_group_all_ok() -> Result<()> {
+    let mut threads = TryThreadGroup::<u32, std::num::ParseIntError>::with_id(format!(
+        "{}:{}",
+        module_path!(),
+        line!()
+    ));
+    threads.spawn(|| "401".parse::<u32>())?;
+    threads.spawn(|| "x402".parse::<u32>())?;
+    assert_eq!(threads.all_ok().unwrap_err().variant(), "TaskError");
+    assert_eq!(threads.errors().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_try_thread_group_boxed_error() -> Result<()> {
+    type BoxError = Box<dyn std::error::Error + Send + Sync>;
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = TryThreadGroup::<u32, BoxError>::with_id(id.clone());
+    threads.spawn(|| Ok(401))?;
+    threads.spawn(|| Err(format!("synthetic error at number {}", 402).into()))?;
+    let data = threads.results();
+    assert_eq!(data[0], Ok(401));
+    assert_eq!(
+        data[1].clone().unwrap_err().to_string(),
+        format!("TaskError: task in thread {}:2 failed: synthetic error at number 402", id)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_spawn_with_retry() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<u32>::with_id(id.clone());
+    let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = calls.clone();
+    let policy = RetryPolicy::new(3).fixed(std::time::Duration::from_millis(10));
+    threads.spawn_with_retry(policy, move || {
+        let attempt = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        if attempt < 3 {
+            panic!("synthetic error at attempt {}", attempt)
+        }
+        attempt as u32
+    })?;
+    threads.spawn_with_retry(RetryPolicy::new(2), || panic!("synthetic error"))?;
+
+    assert_eq!(threads.join()?, 3);
+    assert!(threads.join().is_err());
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+    let errors = threads.errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors.contains_key(&format!("{}:{}:2", std::process::id(), id)));
+
+    let attempts = threads.attempt_errors();
+    let first = &attempts[&format!("{}:{}:1", std::process::id(), id)];
+    assert_eq!(first.len(), 2);
+    assert_eq!(
+        first[1],
+        Error::ThreadJoinError {
+            thread: format!("{}:1#2", id),
+            index: 1,
+            message: Some("synthetic error at attempt 2".to_string()),
+        }
+    );
+    assert_eq!(attempts[&format!("{}:{}:2", std::process::id(), id)].len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_spawn_with_retry_cancelled_during_backoff() -> Result<()> {
+    let mut threads = ThreadGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()));
+    let policy = RetryPolicy::new(3).fixed(std::time::Duration::from_secs(60));
+    threads.spawn_with_retry(policy, || panic!("synthetic error at number {}", 161))?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let cancelled = std::time::Instant::now();
+    threads.cancel();
+    assert!(threads.join().is_err());
+    assert!(cancelled.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(threads.attempt_errors().values().next().unwrap().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_retry_policy_delay() {
+    let policy = RetryPolicy::new(10).exponential(
+        std::time::Duration::from_millis(100),
+        std::time::Duration::from_secs(1),
+    );
+    assert_eq!(policy.delay(1), std::time::Duration::from_millis(100));
+    assert_eq!(policy.delay(3), std::time::Duration::from_millis(400));
+    assert_eq!(policy.delay(5), std::time::Duration::from_secs(1));
+    assert_eq!(RetryPolicy::new(0).max_attempts(), 1);
+}
+
+#[test]
+fn test_status() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<u32>::with_id(id.clone());
+    let token = threads.token();
+    threads.spawn(|| panic!("synthetic error"))?;
+    threads.spawn(|| 402)?;
+    threads.spawn_cancellable(|token| {
+        while !token.is_cancelled() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        403
+    })?;
+    assert!(threads.join().is_err());
+    while threads.status().finished.is_empty() {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    let status = threads.status();
+    assert_eq!(status.running, vec![format!("{}:3", id)]);
+    assert_eq!(status.finished, vec![format!("{}:2", id)]);
+    assert_eq!(status.joined, 1);
+    assert_eq!(status.panicked, 1);
+    assert_eq!(status.total(), 3);
+    assert_eq!(status.done(), 2);
+    assert!(!threads.is_finished());
+
+    token.cancel();
+    while !threads.is_finished() {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(threads.status().finished.len(), 2);
+    assert_eq!(threads.as_far_as_ok(), vec![402, 403]);
+    assert!(threads.is_finished());
+    Ok(())
+}
+
+type Events = std::sync::Arc<std::sync::Mutex<Vec<(&'static str, String)>>>;
+
+fn record(events: &Events, event: &'static str) -> impl Fn(&str, std::time::Duration) {
+    let events = events.clone();
+    move |name, _elapsed| events.lock().unwrap().push((event, name.to_string()))
+}
+
+#[test]
+fn test_hooks() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<u32>::with_id(id.clone());
+    threads.spawn(|| 400)?;
+
+    let events = Events::default();
+    threads.on_start(record(&events, "start"));
+    threads.on_success(record(&events, "success"));
+    threads.on_panic(record(&events, "panic"));
+    threads.on_join(record(&events, "join"));
+    threads.spawn(|| 401)?;
+    threads.spawn(|| panic!("synthetic error"))?;
+    assert_eq!(threads.results().len(), 3);
+
+    let mut events = events.lock().unwrap().clone();
+    events.sort();
+    let name = |index: usize| format!("{}:{}", id, index);
+    assert_eq!(
+        events,
+        vec![
+            ("join", name(2)),
+            ("join", name(3)),
+            ("panic", name(3)),
+            ("start", name(2)),
+            ("start", name(3)),
+            ("success", name(2)),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_stats() -> Result<()> {
+    let mut threads = ThreadGroup::<u64>::with_id(format!("{}:{}", module_path!(), line!()));
+    for number in [3, 1, 2] {
+        threads.spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(number * 100));
+            number
+        })?;
+    }
+    threads.spawn(|| panic!("synthetic error"))?;
+    let data = threads.results_with_stats();
+    assert_eq!(data.len(), 4);
+    for (result, stats) in &data[..3] {
+        let millis = result.clone()? * 100;
+        assert!(stats.duration >= std::time::Duration::from_millis(millis));
+    }
+    assert!(data[3].0.is_err());
+
+    let stats = threads.stats();
+    assert_eq!(stats.tasks.len(), 4);
+    assert!(stats.elapsed >= std::time::Duration::from_millis(300));
+    let duration = stats.duration.unwrap();
+    assert!(duration.min < std::time::Duration::from_millis(100));
+    assert!(duration.max >= std::time::Duration::from_millis(300));
+    assert!(duration.mean >= std::time::Duration::from_millis(150));
+    assert_eq!(duration.p50, stats.percentile(50.0).unwrap());
+    assert!(duration.p50 >= std::time::Duration::from_millis(100));
+    assert_eq!(duration.p99, duration.max);
+    assert!(stats.queue_delay.is_some());
+    Ok(())
+}
+
+#[test]
+fn test_percentile() {
+    let durations = (1..=10).map(std::time::Duration::from_secs).collect::<Vec<_>>();
+    assert_eq!(thread_groups::percentile(&durations, 50.0), Some(durations[4]));
+    assert_eq!(thread_groups::percentile(&durations, 90.0), Some(durations[8]));
+    assert_eq!(thread_groups::percentile(&durations, 0.0), Some(durations[0]));
+    assert_eq!(thread_groups::percentile(&durations, 100.0), Some(durations[9]));
+    assert_eq!(thread_groups::percentile(&[], 50.0), None);
+}
+
+#[test]
+fn test_child() -> Result<()> {
+    let mut parent = ThreadGroup::<u32>::with_id("parent".to_string());
+    let mut child = parent.child::<String>("child");
+    let mut grandchild = child.child::<()>("grandchild");
+    assert_eq!(child.to_string(), "thread_groups::ThreadGroup parent/child");
+
+    let finished = std::sync::Arc::new(std::sync::Mutex::new(Vec::<&'static str>::new()));
+    child.spawn_cancellable({
+        let finished = finished.clone();
+        move |token| {
+            while !token.is_cancelled() {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            finished.lock().unwrap().push("child");
+            std::thread::current().name().unwrap().to_string()
+        }
+    })?;
+    grandchild.spawn(|| panic!("synthetic error at number {}", 203))?;
+    assert!(grandchild.join().is_err());
+    parent.spawn(|| 201)?;
+    assert!(!child.is_cancelled());
+    parent.cancel();
+    assert!(child.is_cancelled());
+    assert!(grandchild.is_cancelled());
+
+    assert_eq!(parent.results(), vec![Ok(201)]);
+    assert_eq!(*finished.lock().unwrap(), vec!["child"]);
+    assert_eq!(child.join()?, "parent/child:1");
+
+    let id = format!("{}:parent/child/grandchild:1", std::process::id());
+    for errors in [parent.errors(), child.errors(), grandchild.errors()] {
+        assert_eq!(errors.keys().collect::<Vec<_>>(), vec![&id]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_child_is_waited_for_when_exhausting_parent() -> Result<()> {
+    let mut parent = ThreadGroup::<u32>::with_id(format!("{}:{}", module_path!(), line!()));
+    let mut child = parent.child::<()>("child");
+    let done = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let spawn = |child: &mut ThreadGroup<()>| {
+        let done = done.clone();
+        child.spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            done.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        })
+    };
+
+    spawn(&mut child)?;
+    parent.spawn(|| 205)?;
+    assert_eq!(parent.all_ok_fail_fast()?, vec![205]);
+    assert_eq!(done.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    spawn(&mut child)?;
+    parent.spawn(|| 206)?;
+    assert_eq!(parent.drain().collect::<Vec<_>>(), vec![Ok(206)]);
+    assert_eq!(done.load(std::sync::atomic::Ordering::SeqCst), 2);
+    assert_eq!(child.results().len(), 2);
+
+    spawn(&mut child)?;
+    drop(child);
+    assert_eq!(parent.results(), vec![]);
+    assert_eq!(done.load(std::sync::atomic::Ordering::SeqCst), 3);
+    Ok(())
+}
+
+#[test]
+fn test_child_timeout() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut parent = ThreadGroup::<u32>::with_id(id.clone());
+    let mut child = parent.child::<()>("child");
+    child.spawn_cancellable(|token| {
+        while !token.is_cancelled() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+    })?;
+    parent.spawn(|| 207)?;
+    let timeout = std::time::Duration::from_millis(50);
+    let error = parent.results_timeout(timeout).unwrap_err();
+    let running = vec![format!("{}/child:1", id)];
+    assert_eq!(error, Error::Timeout { group: id.clone(), running });
+    assert_eq!(parent.all_ok_timeout(timeout).unwrap_err().variant(), "Timeout");
+    assert_eq!(parent.as_far_as_ok_timeout(timeout).unwrap_err().variant(), "Timeout");
+    assert_eq!(parent.live(), 1);
+    parent.cancel();
+    assert_eq!(parent.results_timeout(std::time::Duration::from_secs(10))?, vec![Ok(207)]);
+    assert_eq!(child.results(), vec![Ok(())]);
+    Ok(())
+}
+
+#[test]
+fn test_drop_policy() -> Result<()> {
+    let panics = std::sync::Arc::new(std::sync::Mutex::new(Vec::<(String, Error)>::new()));
+    let mut threads = ThreadGroupBuilder::new()
+        .id("drop")
+        .drop_policy(DropPolicy::JoinOnDrop)
+        .build::<u32>();
+    threads.on_drop_panic({
+        let panics = panics.clone();
+        move |id, e| panics.lock().unwrap().push((id.to_string(), e))
+    });
+    threads.spawn(|| {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        panic!("synthetic error at number {}", 211)
+    })?;
+    threads.spawn(|| 212)?;
+    drop(threads);
+    assert_eq!(
+        *panics.lock().unwrap(),
+        vec![(
+            format!("{}:drop:1", std::process::id()),
+            Error::ThreadJoinError {
+                thread: "drop:1".to_string(),
+                index: 1,
+                message: Some("synthetic error at number 211".to_string()),
+            }
+        )]
+    );
+
+    let mut threads = ThreadGroup::<u32>::with_id("unjoined".to_string());
+    threads.set_drop_policy(DropPolicy::PanicIfUnjoined);
+    threads.spawn(|| 213)?;
+    let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(threads)));
+    assert_eq!(
+        thread_groups::panic_message(&*payload.unwrap_err()),
+        Some(
+            "thread_groups::ThreadGroup unjoined dropped with unjoined threads: unjoined:1"
+                .to_string()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn test_spawn_producer() -> Result<()> {
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroupBuilder::new().id(id.clone()).channel_capacity(1).build::<u32>();
+    for base in [100, 200] {
+        threads.spawn_producer(move |sender| {
+            for n in 1..=3 {
+                sender.send(base + n).unwrap();
+            }
+        })?;
+    }
+    threads.spawn_producer(|sender| {
+        sender.send(301).unwrap();
+        panic!("synthetic error at number {}", 302)
+    })?;
+    let mut items = threads.receiver().collect::<Vec<u32>>();
+    items.sort();
+    assert_eq!(items, vec![101, 102, 103, 201, 202, 203, 301]);
+    assert_eq!(threads.joined(), 3);
+    assert_eq!(threads.failed(), 1);
+    assert_eq!(
+        threads.errors().keys().collect::<Vec<_>>(),
+        vec![&format!("{}:{}:3", std::process::id(), id)]
+    );
+    assert_eq!(threads.receiver().next(), None);
+    Ok(())
+}
+
+#[test]
+fn test_start_gate() -> Result<()> {
+    let mut threads = ThreadGroup::<usize>::with_start_gate();
+    let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let barrier = threads.barrier();
+    for _ in 0..4 {
+        let counter = counter.clone();
+        let barrier = barrier.clone();
+        threads.spawn(move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            barrier.wait();
+            counter.load(std::sync::atomic::Ordering::SeqCst)
+        })?;
+    }
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 0);
+    threads.release();
+    assert_eq!(threads.results(), vec![Ok(4), Ok(4), Ok(4), Ok(4)]);
+
+    threads.spawn(|| 1)?;
+    assert_eq!(threads.join()?, 1);
+    Ok(())
+}
+
+#[test]
+fn test_start_gate_releases_children() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut parent = ThreadGroup::<u32>::with_start_gate();
+        let mut child = parent.child::<u32>("child");
+        child.spawn(|| 231).unwrap();
+        parent.release();
+        let results = parent.results();
+        sender.send((results, child.results())).unwrap();
+    });
+    let (parent, child) = receiver.recv_timeout(std::time::Duration::from_secs(10)).unwrap();
+    assert_eq!(parent, vec![]);
+    assert_eq!(child, vec![Ok(231)]);
+}
+
+#[test]
+fn test_start_gate_child_created_after_release() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let parent = ThreadGroup::<u32>::with_start_gate();
+        parent.release();
+        let mut child = parent.child::<u32>("child");
+        child.spawn(|| 233).unwrap();
+        while !child.is_finished() {
+            std::thread::yield_now();
+        }
+        sender.send(child.results()).unwrap();
+    });
+    let results = receiver.recv_timeout(std::time::Duration::from_secs(10)).unwrap();
+    assert_eq!(results, vec![Ok(233)]);
+}
+
+#[test]
+fn test_start_gate_released_by_completion_order_joins() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut threads = ThreadGroup::<u32>::with_start_gate();
+        threads.spawn(|| 1).unwrap();
+        let any = threads.join_any();
+        let mut threads = ThreadGroup::<u32>::with_start_gate();
+        threads.spawn(|| 2).unwrap();
+        threads.spawn(|| 3).unwrap();
+        let mut fail_fast = threads.all_ok_fail_fast().unwrap();
+        fail_fast.sort();
+        let mut threads = ThreadGroup::<u32>::with_start_gate();
+        threads.spawn(|| 4).unwrap();
+        let timeout = threads.join_timeout(std::time::Duration::from_secs(5));
+        sender.send((any, fail_fast, timeout)).unwrap();
+    });
+    let (any, fail_fast, timeout) =
+        receiver.recv_timeout(std::time::Duration::from_secs(10)).unwrap();
+    assert_eq!(any, Ok(1));
+    assert_eq!(fail_fast, vec![2, 3]);
+    assert_eq!(timeout, Ok(4));
+}
+
+thread_local! {
+    static REQUEST_ID: std::cell::Cell<Option<u32>> = const { std::cell::Cell::new(None) };
+}
+
+struct RequestId(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+impl ContextPropagator for RequestId {
+    type Context = Option<u32>;
+
+    fn capture(&self) -> Option<u32> {
+        REQUEST_ID.get()
+    }
+
+    fn install(&self, context: Option<u32>) {
+        REQUEST_ID.set(context);
+    }
+
+    fn teardown(&self) {
+        REQUEST_ID.set(None);
+        self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_propagate_context() -> Result<()> {
+    let teardowns = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let id = format!("{}:{}", module_path!(), line!());
+    let mut threads = ThreadGroup::<Option<u32>>::with_id(id.clone());
+    threads.propagate_context(RequestId(teardowns.clone()));
+    REQUEST_ID.set(Some(241));
+    threads.spawn(|| REQUEST_ID.get())?;
+    REQUEST_ID.set(Some(242));
+    threads.spawn(|| REQUEST_ID.get())?;
+    threads.spawn(|| panic!("synthetic error at number {:?}", REQUEST_ID.get()))?;
+    REQUEST_ID.set(None);
+
+    let results = threads.results();
+    assert_eq!(results[..2], [Ok(Some(241)), Ok(Some(242))]);
+    assert_eq!(
+        results[2].clone().unwrap_err().to_string(),
+        format!("ThreadJoinError: joining thread {}:3: synthetic error at number Some(242)", id)
+    );
+    assert_eq!(teardowns.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+    REQUEST_ID.set(Some(243));
+    let data = threads.map_chunked(1..=4, 2, |_| REQUEST_ID.get());
+    REQUEST_ID.set(None);
+    assert_eq!(data, vec![Ok(Some(243)); 4]);
+    assert_eq!(teardowns.load(std::sync::atomic::Ordering::SeqCst), 5);
+    Ok(())
+}
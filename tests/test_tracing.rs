@@ -0,0 +1,194 @@
+#![cfg(feature = "tracing")]
+use std::sync::{Arc, Mutex};
+use thread_groups::{Result, RetryPolicy, ThreadGroup};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+type Fields = Vec<(String, String)>;
+
+#[derive(Default)]
+struct Recorded {
+    spans: Vec<(&'static str, Option<u64>, Fields)>,
+    events: Vec<(Option<u64>, Fields)>,
+}
+
+#[derive(Default)]
+struct Recorder {
+    recorded: Arc<Mutex<Recorded>>,
+    current: Arc<Mutex<std::collections::HashMap<std::thread::ThreadId, Vec<u64>>>>,
+}
+impl Recorder {
+    fn current(&self) -> Option<u64> {
+        let current = self.current.lock().unwrap();
+        current.get(&std::thread::current().id()).and_then(|stack| stack.last().copied())
+    }
+}
+
+struct Visitor<'a>(&'a mut Fields);
+impl Visit for Visitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name().to_string(), value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push((field.name().to_string(), format!("{:?}", value)));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = Fields::new();
+        span.record(&mut Visitor(&mut fields));
+        let parent = match span.parent() {
+            Some(parent) => Some(parent.into_u64()),
+            None if span.is_contextual() => self.current(),
+            None => None,
+        };
+        let mut recorded = self.recorded.lock().unwrap();
+        recorded.spans.push((span.metadata().name(), parent, fields));
+        Id::from_u64(recorded.spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::new();
+        event.record(&mut Visitor(&mut fields));
+        let span = self.current();
+        self.recorded.lock().unwrap().events.push((span, fields));
+    }
+
+    fn enter(&self, span: &Id) {
+        let mut current = self.current.lock().unwrap();
+        current.entry(std::thread::current().id()).or_default().push(span.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        let mut current = self.current.lock().unwrap();
+        current.entry(std::thread::current().id()).or_default().pop();
+    }
+}
+
+fn field<'a>(fields: &'a Fields, name: &str) -> Option<&'a str> {
+    fields.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+#[test]
+fn test_tracing() -> Result<()> {
+    let recorder = Recorder::default();
+    let recorded = recorder.recorded.clone();
+    let dispatch = tracing::Dispatch::new(recorder);
+    tracing::dispatcher::with_default(&dispatch, || -> Result<()> {
+        let _request = tracing::info_span!("request").entered();
+        let mut threads = ThreadGroup::<u32>::with_id("traced".to_string());
+        threads.spawn(|| {
+            tracing::info!("working");
+            251
+        })?;
+        threads.spawn(|| panic!("synthetic error at number {}", 252))?;
+        assert_eq!(threads.join()?, 251);
+        assert!(threads.join().is_err());
+        Ok(())
+    })?;
+
+    let recorded = recorded.lock().unwrap();
+    let spans = recorded
+        .spans
+        .iter()
+        .map(|(name, parent, fields)| (*name, *parent, field(fields, "thread")))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        spans,
+        vec![
+            ("request", None, None),
+            ("thread_group", Some(1), Some("traced:1")),
+            ("thread_group", Some(1), Some("traced:2")),
+        ]
+    );
+    assert_eq!(field(&recorded.spans[1].2, "group"), Some("traced"));
+
+    let event = |message: &str, thread: &str| {
+        recorded
+            .events
+            .iter()
+            .find(|(_, fields)| {
+                field(fields, "message") == Some(message) && field(fields, "thread") == Some(thread)
+            })
+            .cloned()
+    };
+    let (span, _) = recorded
+        .events
+        .iter()
+        .find(|(_, fields)| field(fields, "message") == Some("working"))
+        .unwrap();
+    assert_eq!(*span, Some(2));
+
+    let (span, fields) = event("thread panicked", "traced:2").unwrap();
+    assert_eq!(span, Some(3));
+    assert!(field(&fields, "duration").is_some());
+
+    let (span, fields) = event("thread joined", "traced:1").unwrap();
+    assert_eq!(span, Some(1));
+    assert_eq!(field(&fields, "ok"), Some("true"));
+    assert!(field(&fields, "duration").is_some());
+
+    let (_, fields) = event("thread joined", "traced:2").unwrap();
+    assert_eq!(field(&fields, "ok"), Some("false"));
+
+    let (span, fields) = event("thread group error", "traced:2").unwrap();
+    assert_eq!(span, Some(1));
+    assert_eq!(field(&fields, "id"), Some(format!("{}:traced:2", std::process::id()).as_str()));
+    assert!(field(&fields, "duration").is_some());
+    Ok(())
+}
+
+#[test]
+fn test_tracing_retry() -> Result<()> {
+    let recorder = Recorder::default();
+    let recorded = recorder.recorded.clone();
+    let dispatch = tracing::Dispatch::new(recorder);
+    tracing::dispatcher::with_default(&dispatch, || -> Result<()> {
+        let mut threads = ThreadGroup::<usize>::with_id("retried".to_string());
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        threads.spawn_with_retry(RetryPolicy::new(2), move || {
+            let attempt = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            tracing::info!(attempt, "attempt");
+            if attempt < 2 {
+                panic!("synthetic error at attempt {}", attempt)
+            }
+            attempt
+        })?;
+        assert_eq!(threads.join()?, 2);
+        Ok(())
+    })?;
+
+    let recorded = recorded.lock().unwrap();
+    let attempts = recorded
+        .events
+        .iter()
+        .filter(|(_, fields)| field(fields, "message") == Some("attempt"))
+        .map(|(span, fields)| (*span, field(fields, "attempt")))
+        .collect::<Vec<_>>();
+    assert_eq!(attempts, vec![(Some(2), Some("1")), (Some(3), Some("2"))]);
+    let spans = recorded
+        .spans
+        .iter()
+        .map(|(name, parent, fields)| (*name, *parent, field(fields, "thread")))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        spans,
+        vec![
+            ("thread_group", None, Some("retried:1")),
+            ("thread_group_attempt", Some(1), Some("retried:1#1")),
+            ("thread_group_attempt", Some(1), Some("retried:1#2")),
+        ]
+    );
+    Ok(())
+}
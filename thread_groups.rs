@@ -3,85 +3,2615 @@
 //! It provides the [`ThreadGroup`] struct which does all the job for
 //! you so you can wait and enjoy the silence of your life in
 //! the real world.
+//!
+//! The [`TryThreadGroup`] struct handles closures returning
+//! [`std::result::Result`], treating their errors like panics.
+//!
+//! Threads left unjoined when a [`ThreadGroup`] is dropped are
+//! detached unless another [`DropPolicy`] is set.
+//!
+//! The [`ThreadPoolGroup`] struct offers the same methods while
+//! running closures on a bounded number of reusable worker threads.
+//!
+//! The [`scope`] function provides a [`ScopedThreadGroup`] whose
+//! threads may borrow data from the stack.
+//!
+//! With the `async` feature enabled, [`ThreadGroup`] implements
+//! `futures_core::Stream` yielding results in completion order and
+//! `ThreadGroup::join_all` returns a future of all results.
+//!
+//! With the `tracing` feature enabled, every thread spawned in a
+//! [`ThreadGroup`] runs within a `thread_group` span whose parent is
+//! the span of the spawning thread, each attempt of
+//! `ThreadGroup::spawn_with_retry` runs within a nested
+//! `thread_group_attempt` span, and joins, panics and errors are
+//! emitted as events.
+
+use std::any::Any;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Display;
+use std::panic::AssertUnwindSafe;
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock, Weak};
+use std::task::Waker;
+use std::thread::{Builder, JoinHandle, Scope, ScopedJoinHandle, Thread, ThreadId};
+use std::time::{Duration, Instant};
+
+/// `thread_id` returns a deterministic name for instances of [`std::thread::Thread`].
+pub fn thread_id(thread: &Thread) -> String {
+    format!(
+        "{}:{}",
+        std::process::id(),
+        thread
+            .name()
+            .map(|a| a.to_string())
+            .unwrap_or_else(|| format!("{:#?}", thread.id()))
+    )
+}
+
+/// `panic_message` returns the message of a panic payload such as
+/// the one returned by [`std::thread::JoinHandle::join`] when the
+/// payload is either a [`&str`] or a [`String`].
+pub fn panic_message(payload: &(dyn Any + Send)) -> Option<String> {
+    payload
+        .downcast_ref::<&str>()
+        .map(|a| a.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+}
+
+fn join_error(task: &TaskId, payload: &(dyn Any + Send)) -> Error {
+    Error::ThreadJoinError {
+        thread: task.name.clone(),
+        index: task.index,
+        message: panic_message(payload),
+    }
+}
+
+fn spawn_error(name: String, error: std::io::Error) -> Error {
+    Error::ThreadSpawnError { name, source: Arc::new(error) }
+}
+
+/// `TaskId` identifies a thread spawned in a group by its index,
+/// in spawn order, and its name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId {
+    index: usize,
+    name: String,
+}
+impl TaskId {
+    /// `TaskId::index` returns the index of the task within its group
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// `TaskId::name` returns the name of the thread running the task
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl std::fmt::Display for TaskId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", &self.name)
+    }
+}
+
+/// `CancellationToken` is shared between a [`ThreadGroup`] and the
+/// closures spawned with [`ThreadGroup::spawn_cancellable`] so that
+/// they can check whether they should stop running.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    parent: Option<Box<CancellationToken>>,
+}
+impl CancellationToken {
+    /// `CancellationToken::new` creates a new token which is not cancelled
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// `CancellationToken::child` creates a new token which is
+    /// cancelled along with this token but which can also be
+    /// cancelled on its own without affecting this token
+    pub fn child(&self) -> CancellationToken {
+        CancellationToken { cancelled: Arc::default(), parent: Some(Box::new(self.clone())) }
+    }
+
+    /// `CancellationToken::cancel` signals every clone of this token
+    /// and of its children that work should stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// `CancellationToken::is_cancelled` returns `true` once
+    /// [`CancellationToken::cancel`] was called on any clone of this
+    /// token or of one of its parents
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+            || self.parent.as_ref().is_some_and(|parent| parent.is_cancelled())
+    }
+}
+
+/// `insert_error` inserts `error` under the key `id`, suffixing the
+/// key with `#2`, `#3` and so on rather than overwriting an existing
+/// entry, and returns the key under which `error` was inserted.
+fn insert_error(errors: &mut BTreeMap<String, Error>, id: String, error: Error) -> String {
+    let mut key = id.clone();
+    let mut attempt = 1;
+    while errors.contains_key(&key) {
+        attempt += 1;
+        key = format!("{}#{}", id, attempt);
+    }
+    errors.insert(key.clone(), error);
+    key
+}
+
+/// `Completions` keeps track of the ids of threads that finished
+/// running, in the order in which they finished.
+#[derive(Default)]
+struct Completions {
+    finished: Mutex<VecDeque<ThreadId>>,
+    signal: Condvar,
+    waker: Mutex<Option<Waker>>,
+}
+impl Completions {
+    fn lock(&self) -> MutexGuard<'_, VecDeque<ThreadId>> {
+        self.finished.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn notify(&self, id: ThreadId) {
+        self.lock().push_back(id);
+        self.signal.notify_all();
+        if let Some(waker) = self.waker.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            waker.wake();
+        }
+    }
+
+    /// `Completions::forget` discards the id of a thread which was
+    /// joined such that only ids of unjoined threads are kept
+    fn forget(&self, id: ThreadId) {
+        let mut finished = self.lock();
+        if let Some(position) = finished.iter().position(|finished| *finished == id) {
+            finished.remove(position);
+        }
+    }
+
+    /// `Completions::register` stores the [`Waker`] to be woken up
+    /// by the next call to [`Completions::notify`], it must be called
+    /// while holding the lock returned by [`Completions::lock`] so
+    /// that notifications cannot be missed.
+    #[cfg_attr(not(feature = "async"), allow(dead_code))]
+    fn register(&self, waker: &Waker) {
+        *self.waker.lock().unwrap_or_else(|e| e.into_inner()) = Some(waker.clone());
+    }
+}
+
+/// `CompletionGuard` notifies [`Completions`] when dropped, which
+/// happens when the thread's closure either returns or panics.
+struct CompletionGuard(Arc<Completions>);
+impl Drop for CompletionGuard {
+    fn drop(&mut self) {
+        self.0.notify(std::thread::current().id());
+    }
+}
+
+/// `Family` links a [`ThreadGroup`] to the groups created with
+/// [`ThreadGroup::child`], keeping track of the names of their
+/// threads which are still running and of the errors bubbled up
+/// from them.
+///
+/// Children are held weakly, they stay alive while either their
+/// group, one of their running threads or one of their own children
+/// is alive.
+#[derive(Default)]
+struct Family {
+    running: Mutex<Vec<String>>,
+    signal: Condvar,
+    waker: Mutex<Option<Waker>>,
+    parent: Option<Arc<Family>>,
+    children: Mutex<Vec<Weak<Family>>>,
+    errors: Mutex<BTreeMap<String, Error>>,
+    gate: Option<Arc<Gate>>,
+}
+impl Family {
+    fn child(self: &Arc<Family>) -> Arc<Family> {
+        let mut children = self.children.lock().unwrap_or_else(|e| e.into_inner());
+        let child = Arc::new(Family {
+            parent: Some(self.clone()),
+            gate: self.gate.as_ref().map(|gate| gate.child()),
+            ..Family::default()
+        });
+        children.retain(|child| child.strong_count() > 0);
+        children.push(Arc::downgrade(&child));
+        child
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<String>> {
+        self.running.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn enter(self: &Arc<Family>, name: &str) -> FamilyGuard {
+        self.lock().push(name.to_string());
+        FamilyGuard(self.clone(), name.to_string())
+    }
+
+    /// `Family::children` returns the children which are still
+    /// alive, discarding the others
+    fn children(&self) -> Vec<Arc<Family>> {
+        let mut children = self.children.lock().unwrap_or_else(|e| e.into_inner());
+        children.retain(|child| child.strong_count() > 0);
+        children.iter().filter_map(Weak::upgrade).collect()
+    }
+
+    /// `Family::release` releases the start gate of this group and of
+    /// every descendant group
+    fn release(&self) {
+        if let Some(gate) = &self.gate {
+            gate.release();
+        }
+        for child in self.children() {
+            child.release();
+        }
+    }
+
+    /// `Family::running` returns the names of the threads of every
+    /// descendant group which are still running
+    fn running(&self) -> Vec<String> {
+        let mut running = Vec::<String>::new();
+        for child in self.children() {
+            running.extend(child.lock().iter().cloned());
+            running.extend(child.running());
+        }
+        running
+    }
+
+    /// `Family::wait` waits in blocking fashion for the threads of
+    /// every descendant group to finish running
+    fn wait(&self) {
+        let _ = self.wait_deadline(None);
+    }
+
+    /// `Family::wait_deadline` waits in blocking fashion for the
+    /// threads of every descendant group to finish running until
+    /// `deadline` if any, returning the names of the threads still
+    /// running by then
+    fn wait_deadline(&self, deadline: Option<Instant>) -> std::result::Result<(), Vec<String>> {
+        for child in self.children() {
+            let mut running = child.lock();
+            while !running.is_empty() {
+                running = match deadline {
+                    None => child.signal.wait(running).unwrap_or_else(|e| e.into_inner()),
+                    Some(deadline) => {
+                        let now = Instant::now();
+                        if now >= deadline {
+                            drop(running);
+                            return Err(self.running());
+                        }
+                        child
+                            .signal
+                            .wait_timeout(running, deadline - now)
+                            .unwrap_or_else(|e| e.into_inner())
+                            .0
+                    },
+                };
+            }
+            drop(running);
+            child.wait_deadline(deadline)?;
+        }
+        Ok(())
+    }
+
+    /// `Family::register` returns `true` if the threads of every
+    /// descendant group finished running, otherwise it stores the
+    /// [`Waker`] to be woken up when a thread of the first descendant
+    /// group found running finishes
+    #[cfg(feature = "async")]
+    fn register(&self, waker: &Waker) -> bool {
+        for child in self.children() {
+            let running = child.lock();
+            if !running.is_empty() {
+                *child.waker.lock().unwrap_or_else(|e| e.into_inner()) = Some(waker.clone());
+                return false;
+            }
+            drop(running);
+            if !child.register(waker) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// `Family::bubble` records `error` in every ancestor group
+    fn bubble(&self, id: &str, error: &Error) {
+        let mut parent = self.parent.as_deref();
+        while let Some(family) = parent {
+            let mut errors = family.errors.lock().unwrap_or_else(|e| e.into_inner());
+            insert_error(&mut errors, id.to_string(), error.clone());
+            drop(errors);
+            parent = family.parent.as_deref();
+        }
+    }
+}
+
+/// `FamilyGuard` marks a thread of a [`Family`] as running until
+/// dropped, which happens when the thread's closure either returns
+/// or panics.
+struct FamilyGuard(Arc<Family>, String);
+impl Drop for FamilyGuard {
+    fn drop(&mut self) {
+        let mut running = self.0.lock();
+        if let Some(position) = running.iter().position(|name| *name == self.1) {
+            running.remove(position);
+        }
+        let waker = self.0.waker.lock().unwrap_or_else(|e| e.into_inner()).take();
+        drop(running);
+        self.0.signal.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// `Gate` parks the threads of a [`ThreadGroup`] created with
+/// [`ThreadGroup::with_start_gate`] until it is released.
+#[derive(Default)]
+struct Gate {
+    released: Mutex<bool>,
+    signal: Condvar,
+}
+impl Gate {
+    fn wait(&self) {
+        let mut released = self.released.lock().unwrap_or_else(|e| e.into_inner());
+        while !*released {
+            released = self.signal.wait(released).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// `Gate::child` creates the gate of a subgroup, which starts
+    /// out released if this gate already is
+    fn child(&self) -> Arc<Gate> {
+        let released = *self.released.lock().unwrap_or_else(|e| e.into_inner());
+        Arc::new(Gate { released: Mutex::new(released), signal: Condvar::new() })
+    }
+
+    fn release(&self) {
+        *self.released.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        self.signal.notify_all();
+    }
+}
+
+/// `GroupBarrier` is a rendezvous point for the threads of a
+/// [`ThreadGroup`], see [`ThreadGroup::barrier`]
+#[derive(Debug, Default)]
+pub struct GroupBarrier {
+    state: Mutex<BarrierState>,
+    signal: Condvar,
+}
+#[derive(Debug, Default)]
+struct BarrierState {
+    parties: usize,
+    arrived: usize,
+    generation: usize,
+}
+impl GroupBarrier {
+    /// `GroupBarrier::wait` blocks the current thread until every
+    /// running thread of the group called `wait` as well, returning
+    /// `true` in the last thread to arrive and `false` in the others.
+    ///
+    /// Threads which finish running without calling `wait` are no
+    /// longer waited for. The barrier can be reused once all threads
+    /// were released.
+    pub fn wait(&self) -> bool {
+        let mut state = self.lock();
+        state.arrived += 1;
+        if GroupBarrier::trip(&mut state) {
+            self.signal.notify_all();
+            return true;
+        }
+        let generation = state.generation;
+        while state.generation == generation {
+            state = self.signal.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        false
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BarrierState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn trip(state: &mut BarrierState) -> bool {
+        if state.arrived == 0 || state.arrived < state.parties {
+            return false;
+        }
+        state.arrived = 0;
+        state.generation += 1;
+        true
+    }
+
+    fn enter(self: &Arc<GroupBarrier>) -> BarrierGuard {
+        self.lock().parties += 1;
+        BarrierGuard(self.clone())
+    }
+}
+
+/// `BarrierGuard` counts a thread as a party of a [`GroupBarrier`]
+/// until dropped, which happens when the thread's closure either
+/// returns or panics.
+struct BarrierGuard(Arc<GroupBarrier>);
+impl Drop for BarrierGuard {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.parties -= 1;
+        if GroupBarrier::trip(&mut state) {
+            self.0.signal.notify_all();
+        }
+    }
+}
+
+/// `ThreadOptions` are the per-thread options used by
+/// [`ThreadGroup::spawn_with`], options left unset fall back to the
+/// group's defaults configured through [`ThreadGroupBuilder`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadOptions {
+    name: Option<String>,
+    stack_size: Option<usize>,
+}
+impl ThreadOptions {
+    /// `ThreadOptions::new` creates options which fall back entirely
+    /// to the group's defaults
+    pub fn new() -> ThreadOptions {
+        ThreadOptions::default()
+    }
+
+    /// `ThreadOptions::name` sets the name of the thread instead of
+    /// the one generated from the group's naming template
+    pub fn name(mut self, name: impl Into<String>) -> ThreadOptions {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// `ThreadOptions::stack_size` sets the stack size of the thread
+    /// in bytes, see [`std::thread::Builder::stack_size`]
+    pub fn stack_size(mut self, stack_size: usize) -> ThreadOptions {
+        self.stack_size = Some(stack_size);
+        self
+    }
+}
+
+/// `ThreadGroupBuilder` configures a [`ThreadGroup`] before creating it
+pub struct ThreadGroupBuilder {
+    id: Option<String>,
+    name_template: String,
+    options: ThreadOptions,
+    cancel_on_failure: bool,
+    drop_policy: DropPolicy,
+    channel_capacity: usize,
+    start_gate: bool,
+}
+impl ThreadGroupBuilder {
+    /// `ThreadGroupBuilder::new` creates a builder whose groups behave
+    /// like ones created with [`ThreadGroup::new`]
+    pub fn new() -> ThreadGroupBuilder {
+        ThreadGroupBuilder {
+            id: None,
+            name_template: "{group}:{index}".to_string(),
+            options: ThreadOptions::default(),
+            cancel_on_failure: false,
+            drop_policy: DropPolicy::default(),
+            channel_capacity: 64,
+            start_gate: false,
+        }
+    }
+
+    /// `ThreadGroupBuilder::id` sets the id of the group
+    pub fn id(mut self, id: impl Into<String>) -> ThreadGroupBuilder {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// `ThreadGroupBuilder::name_template` sets the template from which
+    /// thread names are generated, where `{group}` is replaced with
+    /// the group id and `{index}` with the index of the thread.
+    ///
+    /// Defaults to `"{group}:{index}"`
+    pub fn name_template(mut self, template: impl Into<String>) -> ThreadGroupBuilder {
+        self.name_template = template.into();
+        self
+    }
+
+    /// `ThreadGroupBuilder::stack_size` sets the default stack size in
+    /// bytes of every thread spawned in the group
+    pub fn stack_size(mut self, stack_size: usize) -> ThreadGroupBuilder {
+        self.options.stack_size = Some(stack_size);
+        self
+    }
+
+    /// `ThreadGroupBuilder::options` sets the default [`ThreadOptions`]
+    /// of every thread spawned in the group
+    pub fn options(mut self, options: ThreadOptions) -> ThreadGroupBuilder {
+        self.options = options;
+        self
+    }
+
+    /// `ThreadGroupBuilder::cancel_on_failure` see [`ThreadGroup::set_cancel_on_failure`]
+    pub fn cancel_on_failure(mut self, cancel_on_failure: bool) -> ThreadGroupBuilder {
+        self.cancel_on_failure = cancel_on_failure;
+        self
+    }
+
+    /// `ThreadGroupBuilder::drop_policy` see [`ThreadGroup::set_drop_policy`]
+    pub fn drop_policy(mut self, drop_policy: DropPolicy) -> ThreadGroupBuilder {
+        self.drop_policy = drop_policy;
+        self
+    }
+
+    /// `ThreadGroupBuilder::channel_capacity` see [`ThreadGroup::set_channel_capacity`]
+    pub fn channel_capacity(mut self, channel_capacity: usize) -> ThreadGroupBuilder {
+        self.channel_capacity = channel_capacity;
+        self
+    }
+
+    /// `ThreadGroupBuilder::start_gate` see [`ThreadGroup::with_start_gate`]
+    pub fn start_gate(mut self, start_gate: bool) -> ThreadGroupBuilder {
+        self.start_gate = start_gate;
+        self
+    }
+
+    /// `ThreadGroupBuilder::build` creates the [`ThreadGroup`]
+    pub fn build<T: Send + Sync + 'static>(self) -> ThreadGroup<T> {
+        let mut group = ThreadGroup::with_id(
+            self.id.unwrap_or_else(|| thread_id(&std::thread::current())),
+        );
+        group.name_template = self.name_template;
+        group.options = self.options;
+        group.cancel_on_failure = self.cancel_on_failure;
+        group.drop_policy = self.drop_policy;
+        group.channel_capacity = self.channel_capacity;
+        if self.start_gate {
+            group.family = Arc::new(Family { gate: Some(Arc::default()), ..Family::default() });
+        }
+        group
+    }
+}
+
+impl Default for ThreadGroupBuilder {
+    fn default() -> ThreadGroupBuilder {
+        Self::new()
+    }
+}
+
+/// `Backoff` is the delay strategy of a [`RetryPolicy`] between attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    None,
+    Fixed(Duration),
+    Exponential { initial: Duration, max: Duration },
+}
+
+/// `RetryPolicy` configures how many times
+/// [`ThreadGroup::spawn_with_retry`] runs a closure which panics and
+/// how long it waits between attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: usize,
+    backoff: Backoff,
+}
+impl RetryPolicy {
+    /// `RetryPolicy::new` creates a policy which runs a closure at most
+    /// `max_attempts` times without waiting between attempts
+    pub fn new(max_attempts: usize) -> RetryPolicy {
+        RetryPolicy { max_attempts: max_attempts.max(1), backoff: Backoff::None }
+    }
+
+    /// `RetryPolicy::fixed` waits `delay` between attempts
+    pub fn fixed(mut self, delay: Duration) -> RetryPolicy {
+        self.backoff = Backoff::Fixed(delay);
+        self
+    }
+
+    /// `RetryPolicy::exponential` waits `initial` after the first
+    /// attempt, doubling the delay after each attempt up to `max`
+    pub fn exponential(mut self, initial: Duration, max: Duration) -> RetryPolicy {
+        self.backoff = Backoff::Exponential { initial, max };
+        self
+    }
+
+    /// `RetryPolicy::max_attempts` returns the maximum number of attempts
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// `RetryPolicy::delay` returns how long to wait after the
+    /// failure of `attempt`, starting at 1
+    pub fn delay(&self, attempt: usize) -> Duration {
+        match self.backoff {
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max } => {
+                let exponent = attempt.saturating_sub(1).min(31) as u32;
+                initial.saturating_mul(2u32.pow(exponent)).min(max)
+            },
+        }
+    }
+}
+
+/// `DropPolicy` determines what happens to the threads of a
+/// [`ThreadGroup`] which were not joined by the time it is dropped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// `Detach` leaves the threads running, losing their results
+    /// and panics
+    #[default]
+    Detach,
+    /// `JoinOnDrop` joins the threads in blocking fashion, reporting
+    /// their panics to the [`DropHandler`] registered with
+    /// [`ThreadGroup::on_drop_panic`] or to stderr if there is none
+    JoinOnDrop,
+    /// `PanicIfUnjoined` panics unless the group is dropped while
+    /// already panicking, leaving the threads running
+    PanicIfUnjoined,
+    /// `LogIfUnjoined` writes the names of the threads to stderr,
+    /// leaving them running
+    LogIfUnjoined,
+}
+
+/// `DropHandler` is a callback registered with
+/// [`ThreadGroup::on_drop_panic`] which is called with the id and the
+/// error of every thread found to have panicked when a group whose
+/// [`DropPolicy`] is [`DropPolicy::JoinOnDrop`] is dropped
+pub type DropHandler = Arc<dyn Fn(&str, Error) + Send + Sync>;
+
+/// `ContextPropagator` carries state such as thread-local request
+/// ids from the thread spawning a thread in a [`ThreadGroup`] into
+/// the spawned thread, see [`ThreadGroup::propagate_context`]
+pub trait ContextPropagator: Send + Sync + 'static {
+    /// `Context` is the state captured on the spawning thread
+    type Context: Send + 'static;
+
+    /// `ContextPropagator::capture` is called on the spawning thread
+    /// when a thread is spawned
+    fn capture(&self) -> Self::Context;
+
+    /// `ContextPropagator::install` is called on the spawned thread
+    /// with the captured context before the closure runs
+    fn install(&self, context: Self::Context);
+
+    /// `ContextPropagator::teardown` is called on the spawned thread
+    /// after the closure either returns or panics
+    fn teardown(&self) {}
+}
+
+/// `Propagate` erases the [`ContextPropagator::Context`] type such
+/// that propagators of different types can be registered together.
+trait Propagate: Send + Sync {
+    fn capture(self: Arc<Self>) -> Box<dyn FnOnce() -> Installed + Send>;
+    fn teardown(&self);
+}
+impl<P: ContextPropagator> Propagate for P {
+    fn capture(self: Arc<Self>) -> Box<dyn FnOnce() -> Installed + Send> {
+        let context = ContextPropagator::capture(&*self);
+        Box::new(move || {
+            self.install(context);
+            Installed(self)
+        })
+    }
+
+    fn teardown(&self) {
+        ContextPropagator::teardown(self)
+    }
+}
+
+/// `Installed` tears down the context installed by a
+/// [`ContextPropagator`] when dropped.
+struct Installed(Arc<dyn Propagate>);
+impl Drop for Installed {
+    fn drop(&mut self) {
+        self.0.teardown();
+    }
+}
+
+/// `Contexts` installs the contexts captured by every registered
+/// [`ContextPropagator`] and tears them down in reverse order when
+/// dropped.
+struct Contexts(Vec<Installed>);
+impl Contexts {
+    fn capture(propagators: &[Arc<dyn Propagate>]) -> Vec<Box<dyn FnOnce() -> Installed + Send>> {
+        propagators.iter().map(|propagator| propagator.clone().capture()).collect()
+    }
+
+    fn install(captured: Vec<Box<dyn FnOnce() -> Installed + Send>>) -> Contexts {
+        Contexts(captured.into_iter().map(|install| install()).collect())
+    }
+}
+impl Drop for Contexts {
+    fn drop(&mut self) {
+        while let Some(installed) = self.0.pop() {
+            drop(installed);
+        }
+    }
+}
+
+/// `Hook` is a callback registered on a [`ThreadGroup`] which is
+/// called with the name of a thread and an elapsed time, see
+/// [`ThreadGroup::on_start`], [`ThreadGroup::on_success`],
+/// [`ThreadGroup::on_panic`] and [`ThreadGroup::on_join`]
+pub type Hook = Arc<dyn Fn(&str, Duration) + Send + Sync>;
+
+#[derive(Clone, Default)]
+struct Hooks {
+    start: Vec<Hook>,
+    success: Vec<Hook>,
+    panic: Vec<Hook>,
+    join: Vec<Hook>,
+}
+impl Hooks {
+    fn fire(hooks: &[Hook], name: &str, elapsed: Duration) {
+        for hook in hooks {
+            hook(name, elapsed);
+        }
+    }
+}
+
+/// `Clock` records when a thread was spawned, started running its
+/// closure and finished running it.
+struct Clock {
+    spawned: Instant,
+    started: OnceLock<Instant>,
+    finished: OnceLock<Instant>,
+}
+impl Clock {
+    fn stats(&self) -> Option<TaskStats> {
+        let started = *self.started.get()?;
+        let finished = *self.finished.get()?;
+        Some(TaskStats { queue_delay: started - self.spawned, duration: finished - started })
+    }
+}
+
+/// `Running` records the time at which the closure finished in its
+/// [`Clock`] when dropped, calling the `on_panic` hooks if the thread
+/// is unwinding from a panic in the closure.
+struct Running<'a> {
+    hooks: &'a Hooks,
+    name: &'a str,
+    clock: &'a Clock,
+    started: Instant,
+}
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        let finished = Instant::now();
+        let _ = self.clock.finished.set(finished);
+        if std::thread::panicking() {
+            #[cfg(feature = "tracing")]
+            tracing::error!(
+                thread = self.name,
+                duration = ?finished - self.started,
+                "thread panicked"
+            );
+            Hooks::fire(&self.hooks.panic, self.name, finished - self.started);
+        }
+    }
+}
+
+/// `Status` is a snapshot of the progress of a [`ThreadGroup`]
+/// returned by [`ThreadGroup::status`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Status {
+    /// names of the threads which are still running
+    pub running: Vec<String>,
+    /// names of the threads which finished running but were not joined yet
+    pub finished: Vec<String>,
+    /// number of threads joined so far, including the ones that panicked
+    pub joined: usize,
+    /// number of joined threads that panicked
+    pub panicked: usize,
+}
+impl Status {
+    /// `Status::total` returns the number of threads accounted for in
+    /// the snapshot
+    pub fn total(&self) -> usize {
+        self.running.len() + self.finished.len() + self.joined
+    }
+
+    /// `Status::done` returns the number of threads which are no
+    /// longer running, joined or not
+    pub fn done(&self) -> usize {
+        self.finished.len() + self.joined
+    }
+}
+
+/// `TaskStats` are the timings of a thread returned by
+/// [`ThreadGroup::stats`] and [`ThreadGroup::results_with_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaskStats {
+    /// time from the call to spawn until the closure started running
+    pub queue_delay: Duration,
+    /// time the closure took to return or panic
+    pub duration: Duration,
+}
+
+/// `Summary` aggregates a set of durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+impl Summary {
+    /// `Summary::new` aggregates `durations`, returning [`None`] if
+    /// there are none
+    pub fn new(mut durations: Vec<Duration>) -> Option<Summary> {
+        durations.sort();
+        let total = durations.iter().sum::<Duration>();
+        Some(Summary {
+            min: *durations.first()?,
+            max: *durations.last()?,
+            mean: total / durations.len() as u32,
+            p50: percentile(&durations, 50.0)?,
+            p90: percentile(&durations, 90.0)?,
+            p99: percentile(&durations, 99.0)?,
+        })
+    }
+}
+
+/// `percentile` returns the nearest-rank `p`th percentile, between 0
+/// and 100, of the already sorted `durations`
+pub fn percentile(durations: &[Duration], p: f64) -> Option<Duration> {
+    if durations.is_empty() {
+        return None;
+    }
+    let rank = ((p.clamp(0.0, 100.0) / 100.0) * durations.len() as f64).ceil() as usize;
+    Some(durations[rank.max(1) - 1])
+}
+
+/// `Stats` are the timings of the threads of a [`ThreadGroup`] which
+/// finished running, returned by [`ThreadGroup::stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// timings of each thread which finished running
+    pub tasks: BTreeMap<TaskId, TaskStats>,
+    /// time from the first spawn until the last thread finished running
+    pub elapsed: Duration,
+    /// summary of the durations of all threads in `tasks`
+    pub duration: Option<Summary>,
+    /// summary of the queue delays of all threads in `tasks`
+    pub queue_delay: Option<Summary>,
+}
+impl Stats {
+    /// `Stats::percentile` returns the `p`th percentile, between 0
+    /// and 100, of the durations of all threads in `tasks`
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        let mut durations = self.tasks.values().map(|t| t.duration).collect::<Vec<Duration>>();
+        durations.sort();
+        percentile(&durations, p)
+    }
+}
+
+/// `ThreadGroup` is allows spawning several threads and waiting for
+/// their completion through the specialized methods.
+pub struct ThreadGroup<T> {
+    id: String,
+    handles: VecDeque<(TaskId, JoinHandle<T>)>,
+    spawned: usize,
+    joined: usize,
+    failed: usize,
+    errors: BTreeMap<String, Error>,
+    panics: BTreeMap<String, Box<dyn Any + Send>>,
+    completions: Arc<Completions>,
+    token: CancellationToken,
+    cancel_on_failure: bool,
+    name_template: String,
+    options: ThreadOptions,
+    attempts: Arc<Mutex<BTreeMap<String, Vec<Error>>>>,
+    hooks: Arc<Hooks>,
+    records: BTreeMap<TaskId, (Arc<Clock>, Arc<Hooks>)>,
+    family: Arc<Family>,
+    drop_policy: DropPolicy,
+    drop_handler: Option<DropHandler>,
+    producers: VecDeque<(TaskId, JoinHandle<()>)>,
+    channel: Option<(SyncSender<T>, Receiver<T>)>,
+    channel_capacity: usize,
+    barrier: Arc<GroupBarrier>,
+    propagators: Vec<Arc<dyn Propagate>>,
+}
+impl<T: Send + Sync + 'static> ThreadGroup<T> {
+    /// `ThreadGroup::new` creates a new thread group
+    pub fn new() -> ThreadGroup<T> {
+        ThreadGroup::with_id(thread_id(&std::thread::current()))
+    }
+
+    /// `ThreadGroup::with_id` creates a new thread group with a specific id ([`String`])
+    pub fn with_id(id: String) -> ThreadGroup<T> {
+        ThreadGroup {
+            id,
+            handles: VecDeque::new(),
+            errors: BTreeMap::new(),
+            panics: BTreeMap::new(),
+            spawned: 0,
+            joined: 0,
+            failed: 0,
+            completions: Arc::new(Completions::default()),
+            token: CancellationToken::new(),
+            cancel_on_failure: false,
+            name_template: "{group}:{index}".to_string(),
+            options: ThreadOptions::default(),
+            attempts: Arc::new(Mutex::new(BTreeMap::new())),
+            hooks: Arc::new(Hooks::default()),
+            records: BTreeMap::new(),
+            family: Arc::new(Family::default()),
+            drop_policy: DropPolicy::default(),
+            drop_handler: None,
+            producers: VecDeque::new(),
+            channel: None,
+            channel_capacity: 64,
+            barrier: Arc::new(GroupBarrier::default()),
+            propagators: Vec::new(),
+        }
+    }
+
+    /// `ThreadGroup::with_start_gate` creates a new thread group
+    /// whose threads park before running their closure until
+    /// [`ThreadGroup::release`] is called, such that they can all
+    /// start at the same instant.
+    ///
+    /// Joining or waiting for any thread of the group or of its
+    /// subgroups releases the gate as well.
+    pub fn with_start_gate() -> ThreadGroup<T> {
+        ThreadGroupBuilder::new().start_gate(true).build()
+    }
+
+    /// `ThreadGroup::release` lets the threads parked by the start
+    /// gate of a group created with [`ThreadGroup::with_start_gate`]
+    /// run along with the ones parked by the start gates of its
+    /// subgroups, threads spawned afterwards no longer park
+    pub fn release(&self) {
+        self.family.release();
+    }
+
+    /// `ThreadGroup::barrier` returns the [`GroupBarrier`] shared by
+    /// all threads of the group, where every thread spawned in the
+    /// group is a party until it finishes running.
+    ///
+    /// Combined with [`ThreadGroup::with_start_gate`], all threads
+    /// are spawned before any of them can reach the barrier.
+    pub fn barrier(&self) -> Arc<GroupBarrier> {
+        self.barrier.clone()
+    }
+
+    /// `ThreadGroup::child` creates a subgroup whose id is this
+    /// group's id followed by `/` and `name`, such that its threads
+    /// are named like `parent/child:3` by default.
+    ///
+    /// The subgroup inherits this group's thread options, hooks, drop
+    /// policy and cancellation settings and is cancelled along with
+    /// this group. It has a start gate of its own if this group has
+    /// one, which [`ThreadGroup::release`] releases as well and which
+    /// starts out released if this group's gate already is.
+    /// Collecting the results of this group (e.g. through
+    /// [`ThreadGroup::results`]) also waits for the threads of its
+    /// subgroups to finish, and the errors of subgroups appear in
+    /// [`ThreadGroup::errors`] as well.
+    pub fn child<U: Send + Sync + 'static>(&self, name: impl Display) -> ThreadGroup<U> {
+        let mut group = ThreadGroup::with_id(format!("{}/{}", &self.id, name));
+        group.token = self.token.child();
+        group.cancel_on_failure = self.cancel_on_failure;
+        group.name_template = self.name_template.clone();
+        group.options = self.options.clone();
+        group.hooks = self.hooks.clone();
+        group.family = self.family.child();
+        group.drop_policy = self.drop_policy;
+        group.drop_handler = self.drop_handler.clone();
+        group.channel_capacity = self.channel_capacity;
+        group.propagators = self.propagators.clone();
+        group
+    }
+
+    /// `ThreadGroup::set_cancel_on_failure` makes [`ThreadGroup::all_ok`]
+    /// cancel the remaining threads as soon as one of them fails
+    pub fn set_cancel_on_failure(&mut self, cancel_on_failure: bool) {
+        self.cancel_on_failure = cancel_on_failure;
+    }
+
+    /// `ThreadGroup::set_drop_policy` sets the [`DropPolicy`] applied
+    /// to the threads left unjoined when the group is dropped
+    pub fn set_drop_policy(&mut self, drop_policy: DropPolicy) {
+        self.drop_policy = drop_policy;
+    }
+
+    /// `ThreadGroup::on_drop_panic` registers the [`DropHandler`]
+    /// called with the panics of threads joined when the group is
+    /// dropped with [`DropPolicy::JoinOnDrop`]
+    pub fn on_drop_panic<H: Fn(&str, Error) + Send + Sync + 'static>(&mut self, handler: H) {
+        self.drop_handler = Some(Arc::new(handler));
+    }
+
+    /// `ThreadGroup::spawn` spawns a thread, returning its [`TaskId`]
+    pub fn spawn<F: FnOnce() -> T + Send + 'static>(&mut self, func: F) -> Result<TaskId> {
+        self.spawn_with(ThreadOptions::default(), func)
+    }
+
+    /// `ThreadGroup::spawn_with` spawns a thread with specific
+    /// [`ThreadOptions`], returning its [`TaskId`]
+    pub fn spawn_with<F: FnOnce() -> T + Send + 'static>(
+        &mut self,
+        options: ThreadOptions,
+        func: F,
+    ) -> Result<TaskId> {
+        let (task, handle) = self.spawn_thread(options, func)?;
+        self.handles.push_back((task.clone(), handle));
+        Ok(task)
+    }
+
+    fn spawn_thread<R: Send + 'static, F: FnOnce() -> R + Send + 'static>(
+        &mut self,
+        options: ThreadOptions,
+        func: F,
+    ) -> Result<(TaskId, JoinHandle<R>)> {
+        let index = self.spawned + 1;
+        let name = options.name.unwrap_or_else(|| {
+            self.name_template
+                .replace("{group}", &self.id)
+                .replace("{index}", &index.to_string())
+        });
+        let mut builder = Builder::new().name(name.clone());
+        if let Some(stack_size) = options.stack_size.or(self.options.stack_size) {
+            builder = builder.stack_size(stack_size);
+        }
+        let completions = self.completions.clone();
+        let family = self.family.enter(&name);
+        let party = self.barrier.enter();
+        let gate = self.family.gate.clone();
+        let captured = Contexts::capture(&self.propagators);
+        #[cfg(feature = "tracing")]
+        let (dispatch, span) = (
+            tracing::dispatcher::get_default(Clone::clone),
+            tracing::info_span!("thread_group", group = %self.id, thread = %name),
+        );
+        let hooks = self.hooks.clone();
+        let thread = name.clone();
+        let clock = Arc::new(Clock {
+            spawned: Instant::now(),
+            started: OnceLock::new(),
+            finished: OnceLock::new(),
+        });
+        let handle = builder
+            .spawn({
+                let clock = clock.clone();
+                move || {
+                    let _guard = CompletionGuard(completions);
+                    let _family = family;
+                    let _party = party;
+                    #[cfg(feature = "tracing")]
+                    let _span = (tracing::dispatcher::set_default(&dispatch), span.entered());
+                    if let Some(gate) = gate {
+                        gate.wait();
+                    }
+                    let _contexts = Contexts::install(captured);
+                    Hooks::fire(&hooks.start, &thread, clock.spawned.elapsed());
+                    let started = Instant::now();
+                    let _ = clock.started.set(started);
+                    let running = Running { hooks: &hooks, name: &thread, clock: &clock, started };
+                    let t = func();
+                    drop(running);
+                    Hooks::fire(&hooks.success, &thread, started.elapsed());
+                    t
+                }
+            })
+            .map_err(|e| spawn_error(name.clone(), e))?;
+        self.spawned = index;
+        let task = TaskId { index, name };
+        self.records.insert(task.clone(), (clock, self.hooks.clone()));
+        Ok((task, handle))
+    }
+
+    /// `ThreadGroup::set_channel_capacity` sets how many items sent by
+    /// threads spawned with [`ThreadGroup::spawn_producer`] may be
+    /// buffered before sending blocks, it applies from the next call
+    /// to [`ThreadGroup::spawn_producer`] after
+    /// [`ThreadGroup::receiver`] was exhausted.
+    ///
+    /// Defaults to `64`
+    pub fn set_channel_capacity(&mut self, channel_capacity: usize) {
+        self.channel_capacity = channel_capacity;
+    }
+
+    /// `ThreadGroup::spawn_producer` spawns a thread whose closure
+    /// receives a [`SyncSender`] into the group's bounded channel
+    /// through which it can send any number of items as they are
+    /// produced, see [`ThreadGroup::receiver`]
+    pub fn spawn_producer<F: FnOnce(SyncSender<T>) + Send + 'static>(
+        &mut self,
+        func: F,
+    ) -> Result<TaskId> {
+        let capacity = self.channel_capacity;
+        let (sender, _) = self.channel.get_or_insert_with(|| sync_channel(capacity));
+        let sender = sender.clone();
+        let (task, handle) = self.spawn_thread(ThreadOptions::default(), move || func(sender))?;
+        self.producers.push_back((task.clone(), handle));
+        Ok(task)
+    }
+
+    /// `ThreadGroup::receiver` returns an iterator which yields the
+    /// items sent by every thread spawned with
+    /// [`ThreadGroup::spawn_producer`] as they are produced, in
+    /// blocking fashion, until all of those threads finished.
+    ///
+    /// The producer threads are then joined such that their panics
+    /// land in [`ThreadGroup::errors`].
+    pub fn receiver(&mut self) -> Receive<'_, T> {
+        let receiver = self.channel.take().map(|(_, receiver)| receiver);
+        Receive { group: self, receiver }
+    }
+
+    /// `ThreadGroup::propagate_context` registers a
+    /// [`ContextPropagator`] whose context is captured on the current
+    /// thread whenever a thread is spawned and installed in the
+    /// spawned thread before its closure and hooks run, including the
+    /// attempts of [`ThreadGroup::spawn_with_retry`] and the chunks of
+    /// [`ThreadGroup::map_chunked`]
+    pub fn propagate_context<P: ContextPropagator>(&mut self, propagator: P) {
+        self.propagators.push(Arc::new(propagator));
+    }
+
+    /// `ThreadGroup::on_start` registers a [`Hook`] called from every
+    /// subsequently spawned thread before its closure runs, with the
+    /// time elapsed since the thread was spawned
+    pub fn on_start<H: Fn(&str, Duration) + Send + Sync + 'static>(&mut self, hook: H) {
+        Arc::make_mut(&mut self.hooks).start.push(Arc::new(hook));
+    }
+
+    /// `ThreadGroup::on_success` registers a [`Hook`] called from every
+    /// subsequently spawned thread after its closure returns, with
+    /// the time the closure took to run
+    pub fn on_success<H: Fn(&str, Duration) + Send + Sync + 'static>(&mut self, hook: H) {
+        Arc::make_mut(&mut self.hooks).success.push(Arc::new(hook));
+    }
+
+    /// `ThreadGroup::on_panic` registers a [`Hook`] called from every
+    /// subsequently spawned thread while its closure panics, with the
+    /// time the closure ran until then.
+    ///
+    /// A panic within the hook itself aborts the process.
+    pub fn on_panic<H: Fn(&str, Duration) + Send + Sync + 'static>(&mut self, hook: H) {
+        Arc::make_mut(&mut self.hooks).panic.push(Arc::new(hook));
+    }
+
+    /// `ThreadGroup::on_join` registers a [`Hook`] called from the
+    /// joining thread whenever a subsequently spawned thread is
+    /// joined, with the time elapsed since the thread was spawned
+    pub fn on_join<H: Fn(&str, Duration) + Send + Sync + 'static>(&mut self, hook: H) {
+        Arc::make_mut(&mut self.hooks).join.push(Arc::new(hook));
+    }
+
+    /// `ThreadGroup::spawn_cancellable` spawns a thread whose closure
+    /// receives the group's [`CancellationToken`]
+    pub fn spawn_cancellable<F: FnOnce(CancellationToken) -> T + Send + 'static>(
+        &mut self,
+        func: F,
+    ) -> Result<TaskId> {
+        let token = self.token.clone();
+        self.spawn(move || func(token))
+    }
+
+    /// `ThreadGroup::spawn_with_retry` spawns a thread which runs
+    /// `func` in a new thread for each attempt allowed by `policy`
+    /// until one attempt does not panic, returning its [`TaskId`].
+    ///
+    /// The error of each failed attempt is recorded in
+    /// [`ThreadGroup::attempt_errors`] while only the failure of the
+    /// last attempt lands in [`ThreadGroup::errors`]. No further
+    /// attempts are made once the group is cancelled, even while
+    /// waiting for the backoff delay to pass.
+    pub fn spawn_with_retry<F>(&mut self, policy: RetryPolicy, func: F) -> Result<TaskId>
+    where
+        F: Fn() -> T + Clone + Send + 'static,
+    {
+        let index = self.spawned + 1;
+        let stack_size = self.options.stack_size;
+        let attempts = self.attempts.clone();
+        let token = self.token.clone();
+        let propagators = self.propagators.clone();
+        self.spawn(move || {
+            let thread = std::thread::current().name().unwrap_or_default().to_string();
+            let id = format!("{}:{}", std::process::id(), &thread);
+            let record = |e: Error| {
+                let mut attempts = attempts.lock().unwrap_or_else(|e| e.into_inner());
+                attempts.entry(id.clone()).or_default().push(e);
+            };
+            let mut attempt = 0;
+            loop {
+                attempt += 1;
+                let name = format!("{}#{}", &thread, attempt);
+                let mut builder = Builder::new().name(name.clone());
+                if let Some(stack_size) = stack_size {
+                    builder = builder.stack_size(stack_size);
+                }
+                let task = TaskId { index, name };
+                let captured = Contexts::capture(&propagators);
+                #[cfg(feature = "tracing")]
+                let (dispatch, span) = (
+                    tracing::dispatcher::get_default(Clone::clone),
+                    tracing::info_span!("thread_group_attempt", thread = %task.name, attempt),
+                );
+                let func = func.clone();
+                let payload = match builder.spawn(move || {
+                    #[cfg(feature = "tracing")]
+                    let _span = (tracing::dispatcher::set_default(&dispatch), span.entered());
+                    let _contexts = Contexts::install(captured);
+                    func()
+                }) {
+                    Ok(handle) => match handle.join() {
+                        Ok(t) => return t,
+                        Err(payload) => {
+                            record(join_error(&task, &*payload));
+                            payload
+                        },
+                    },
+                    Err(e) => {
+                        let e = spawn_error(task.name, e);
+                        let message = e.to_string();
+                        record(e);
+                        Box::new(message)
+                    },
+                };
+                let retry = Instant::now() + policy.delay(attempt);
+                while attempt < policy.max_attempts() && !token.is_cancelled() {
+                    let now = Instant::now();
+                    if now >= retry {
+                        break;
+                    }
+                    std::thread::sleep((retry - now).min(Duration::from_millis(10)));
+                }
+                if attempt >= policy.max_attempts() || token.is_cancelled() {
+                    std::panic::resume_unwind(payload);
+                }
+            }
+        })
+    }
+
+    /// `ThreadGroup::attempt_errors` returns a [`BTreeMap<String,
+    /// Vec<Error>>`] of the errors of every failed attempt of threads
+    /// spawned with [`ThreadGroup::spawn_with_retry`] whose keys are
+    /// thread ids as in [`ThreadGroup::errors`]
+    pub fn attempt_errors(&self) -> BTreeMap<String, Vec<Error>> {
+        self.attempts.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// `ThreadGroup::cancel` cancels the group's [`CancellationToken`]
+    /// so that threads spawned with
+    /// [`ThreadGroup::spawn_cancellable`] can stop early
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// `ThreadGroup::is_cancelled` returns `true` once
+    /// [`ThreadGroup::cancel`] was called
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// `ThreadGroup::token` returns a clone of the group's [`CancellationToken`]
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// `ThreadGroup::join` waits for the first thread to join in
+    /// blocking fashion, returning the result of that threads
+    /// [`FnOnce`]
+    pub fn join(&mut self) -> Result<T> {
+        let (task, handle) = self
+            .handles
+            .pop_front()
+            .ok_or(Error::ThreadGroupError(format!("no threads in group {}", &self)))?;
+        self.join_handle(task, handle)
+    }
+
+    /// `ThreadGroup::join_id` waits for the thread identified by `id`
+    /// to join in blocking fashion, returning the result of that
+    /// threads [`FnOnce`]
+    pub fn join_id(&mut self, id: &TaskId) -> Result<T> {
+        let position = self.handles.iter().position(|(task, _)| task == id).ok_or(
+            Error::ThreadGroupError(format!("no thread {} in group {}", id, &self)),
+        )?;
+        let (task, handle) = self.handles.remove(position).expect("thread handle");
+        self.join_handle(task, handle)
+    }
+
+    /// `ThreadGroup::join_next_completed` waits for whichever thread
+    /// finishes first in blocking fashion, returning its [`TaskId`]
+    /// along with the result of that threads [`FnOnce`]
+    pub fn join_next_completed(&mut self) -> Result<(TaskId, T)> {
+        if self.handles.is_empty() {
+            return Err(Error::ThreadGroupError(format!("no threads in group {}", &self)));
+        }
+        let position = self.wait_for_completion();
+        let (task, handle) = self.handles.remove(position).expect("completed thread handle");
+        self.join_handle(task.clone(), handle).map(|t| (task, t))
+    }
+
+    /// `ThreadGroup::join_any` waits for whichever thread finishes
+    /// first in blocking fashion, returning the result of that
+    /// threads [`FnOnce`]
+    pub fn join_any(&mut self) -> Result<T> {
+        self.join_next_completed().map(|(_, t)| t)
+    }
+
+    fn wait_for_completion(&self) -> usize {
+        self.release();
+        let mut finished = self.completions.lock();
+        loop {
+            if let Some(position) = self.next_completed(&mut finished) {
+                return position;
+            }
+            finished = self
+                .completions
+                .signal
+                .wait(finished)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// `ThreadGroup::next_completed` returns the position in
+    /// `self.handles` of the first thread that finished running,
+    /// discarding ids of threads which were already joined
+    fn next_completed(&self, finished: &mut VecDeque<ThreadId>) -> Option<usize> {
+        while let Some(id) = finished.pop_front() {
+            if let Some(position) = self.handles.iter().position(|(_, h)| h.thread().id() == id) {
+                return Some(position);
+            }
+        }
+        None
+    }
+
+    /// `ThreadGroup::join_timeout` waits up to `timeout` for the
+    /// first thread to join, returning [`Error::Timeout`] and leaving
+    /// the thread in the group if it is still running by then
+    pub fn join_timeout(&mut self, timeout: Duration) -> Result<T> {
+        self.join_deadline(Instant::now() + timeout)
+    }
+
+    /// `ThreadGroup::join_deadline` waits until `deadline` for the
+    /// first thread to join, returning [`Error::Timeout`] and leaving
+    /// the thread in the group if it is still running by then
+    pub fn join_deadline(&mut self, deadline: Instant) -> Result<T> {
+        self.wait_deadline(1, deadline)?;
+        self.join()
+    }
+
+    /// `ThreadGroup::wait_deadline` waits until the first `count`
+    /// threads in the group finished running or `deadline` passes,
+    /// whichever happens first
+    fn wait_deadline(&self, count: usize, deadline: Instant) -> Result<()> {
+        self.release();
+        let mut finished = self.completions.lock();
+        loop {
+            let pending = self
+                .handles
+                .iter()
+                .take(count)
+                .any(|(_, h)| !finished.contains(&h.thread().id()));
+            if !pending {
+                return Ok(());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                let running = self.running_in(&finished);
+                return Err(Error::Timeout { group: self.id.clone(), running });
+            }
+            finished = self
+                .completions
+                .signal
+                .wait_timeout(finished, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+
+    /// `ThreadGroup::running` returns the names of the threads in the
+    /// group which are still running
+    pub fn running(&self) -> Vec<String> {
+        self.running_in(&self.completions.lock())
+    }
+
+    /// `ThreadGroup::status` returns a [`Status`] snapshot of the
+    /// group without joining any thread
+    pub fn status(&self) -> Status {
+        let (finished, running): (Vec<_>, Vec<_>) =
+            self.handles.iter().partition(|(_, h)| h.is_finished());
+        Status {
+            running: running.into_iter().map(|(task, _)| task.name.clone()).collect(),
+            finished: finished.into_iter().map(|(task, _)| task.name.clone()).collect(),
+            joined: self.joined,
+            panicked: self.failed,
+        }
+    }
+
+    /// `ThreadGroup::is_finished` returns `true` when every thread in
+    /// the group finished running, so that joining them does not block
+    pub fn is_finished(&self) -> bool {
+        self.handles.iter().all(|(_, h)| h.is_finished())
+    }
+
+    fn running_in(&self, finished: &VecDeque<ThreadId>) -> Vec<String> {
+        self.handles
+            .iter()
+            .filter(|(_, h)| !finished.contains(&h.thread().id()))
+            .map(|(task, _)| task.name.clone())
+            .collect()
+    }
+
+    fn join_handle<R>(&mut self, task: TaskId, handle: JoinHandle<R>) -> Result<R> {
+        let id = thread_id(handle.thread());
+        let thread = handle.thread().id();
+        self.release();
+
+        let joined = handle.join();
+        self.completions.forget(thread);
+        let end = match joined {
+            Ok(t) => Ok(t),
+            Err(payload) => {
+                let e = join_error(&task, &*payload);
+                let key = self.record_error(id, e.clone());
+                self.panics.insert(key, payload);
+                self.failed += 1;
+                Err(e)
+            },
+        };
+        self.joined += 1;
+        if let Some((clock, hooks)) = self.records.get(&task) {
+            #[cfg(feature = "tracing")]
+            tracing::info!(
+                group = %self.id,
+                thread = %task.name,
+                duration = ?clock.stats().unwrap_or_default().duration,
+                elapsed = ?clock.spawned.elapsed(),
+                ok = end.is_ok(),
+                "thread joined"
+            );
+            Hooks::fire(&hooks.join, &task.name, clock.spawned.elapsed());
+        }
+        end
+    }
+
+    /// `ThreadGroup::spawned` returns the number of threads ever
+    /// spawned in the group
+    pub fn spawned(&self) -> usize {
+        self.spawned
+    }
+
+    /// `ThreadGroup::live` returns the number of threads in the group
+    /// which were not joined yet, regardless of whether they are
+    /// still running
+    pub fn live(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// `ThreadGroup::joined` returns the number of threads joined so
+    /// far, including the ones that panicked
+    pub fn joined(&self) -> usize {
+        self.joined
+    }
+
+    /// `ThreadGroup::failed` returns the number of joined threads that
+    /// panicked
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    /// `ThreadGroup::results` waits for the all threads to join in
+    /// blocking fashion, returning all their results at once as a [`Vec<Result<T>>`]
+    pub fn results(&mut self) -> Vec<Result<T>> {
+        let mut val = Vec::<Result<T>>::new();
+        while !self.handles.is_empty() {
+            val.push(self.join());
+        }
+        self.wait_children();
+        val
+    }
+
+    /// `ThreadGroup::map` spawns one thread per item calling `func`
+    /// with it and waits for those threads to join in blocking
+    /// fashion, returning their results as a [`Vec<Result<T>>`] in the
+    /// order of `items`
+    pub fn map<I, F>(&mut self, items: I, func: F) -> Vec<Result<T>>
+    where
+        I: IntoIterator,
+        I::Item: Send + 'static,
+        F: Fn(I::Item) -> T + Send + Sync + 'static,
+    {
+        let func = Arc::new(func);
+        let tasks = items
+            .into_iter()
+            .map(|item| {
+                let func = func.clone();
+                self.spawn(move || func(item))
+            })
+            .collect::<Vec<Result<TaskId>>>();
+        tasks
+            .into_iter()
+            .map(|task| {
+                let task = task.inspect_err(|e| self.record_spawn_error(e))?;
+                self.join_id(&task)
+            })
+            .collect()
+    }
+
+    /// `ThreadGroup::try_map` is like [`ThreadGroup::map`] but returns
+    /// all the OK results at once as a [`Vec<T>`] if there are no errors.
+    pub fn try_map<I, F>(&mut self, items: I, func: F) -> Result<Vec<T>>
+    where
+        I: IntoIterator,
+        I::Item: Send + 'static,
+        F: Fn(I::Item) -> T + Send + Sync + 'static,
+    {
+        self.map(items, func).into_iter().collect()
+    }
+
+    /// `ThreadGroup::map_chunked` is like [`ThreadGroup::map`] but
+    /// splits `items` in up to `threads` contiguous chunks each
+    /// processed by a single thread. When a thread panics, every item
+    /// of its chunk results in the same error.
+    pub fn map_chunked<I, F>(&mut self, items: I, threads: usize, func: F) -> Vec<Result<T>>
+    where
+        I: IntoIterator,
+        I::Item: Send + 'static,
+        F: Fn(I::Item) -> T + Send + Sync + 'static,
+    {
+        let items = items.into_iter().collect::<Vec<I::Item>>();
+        let size = items.len().div_ceil(threads.max(1)).max(1);
+        let mut chunks = Vec::<Vec<I::Item>>::new();
+        for item in items {
+            match chunks.last_mut() {
+                Some(chunk) if chunk.len() < size => chunk.push(item),
+                _ => chunks.push(vec![item]),
+            }
+        }
+        let lengths = chunks.iter().map(|chunk| chunk.len()).collect::<Vec<usize>>();
+
+        let func = Arc::new(func);
+        let spawned = chunks
+            .into_iter()
+            .map(|chunk| {
+                let func = func.clone();
+                self.spawn_thread(ThreadOptions::default(), move || {
+                    chunk.into_iter().map(|item| func(item)).collect::<Vec<T>>()
+                })
+            })
+            .collect::<Vec<Result<(TaskId, JoinHandle<Vec<T>>)>>>();
+        let mut val = Vec::<Result<T>>::new();
+        for (chunk, spawned) in lengths.into_iter().zip(spawned) {
+            let end = spawned
+                .inspect_err(|e| self.record_spawn_error(e))
+                .and_then(|(task, handle)| self.join_handle(task, handle));
+            match end {
+                Ok(data) => val.extend(data.into_iter().map(Ok)),
+                Err(e) => val.extend(std::iter::repeat_n(e, chunk).map(Err)),
+            }
+        }
+        val
+    }
+
+    fn record_spawn_error(&mut self, error: &Error) {
+        let id = match error {
+            Error::ThreadSpawnError { name, .. } => format!("{}:{}", std::process::id(), name),
+            _ => self.id.clone(),
+        };
+        self.record_error(id, error.clone());
+    }
+
+    /// `ThreadGroup::record_error` inserts `error` in
+    /// [`ThreadGroup::errors`] and in the errors of every ancestor
+    /// group, returning the key under which it was inserted
+    fn record_error(&mut self, id: String, error: Error) -> String {
+        self.family.bubble(&id, &error);
+        let key = insert_error(&mut self.errors, id, error);
+        #[cfg(feature = "tracing")]
+        if let Some(error) = self.errors.get(&key) {
+            let thread = match error {
+                Error::ThreadJoinError { thread, .. }
+                | Error::TaskError { thread, .. }
+                | Error::ThreadSpawnError { name: thread, .. } => Some(thread.as_str()),
+                _ => None,
+            };
+            let duration = self
+                .records
+                .iter()
+                .find(|(task, _)| Some(task.name.as_str()) == thread)
+                .and_then(|(_, (clock, _))| clock.stats())
+                .map(|stats| stats.duration);
+            tracing::error!(
+                group = %self.id,
+                id = %key,
+                thread,
+                duration = ?duration,
+                error = %error,
+                "thread group error"
+            );
+        }
+        key
+    }
+
+    /// `ThreadGroup::wait_children` waits in blocking fashion for the
+    /// threads of every subgroup created with [`ThreadGroup::child`]
+    /// to finish
+    fn wait_children(&self) {
+        self.release();
+        self.family.wait();
+    }
+
+    /// `ThreadGroup::wait_children_deadline` waits until `deadline`
+    /// for the threads of every subgroup created with
+    /// [`ThreadGroup::child`] to finish, returning [`Error::Timeout`]
+    /// with the names of those still running by then
+    fn wait_children_deadline(&self, deadline: Instant) -> Result<()> {
+        self.release();
+        self.family
+            .wait_deadline(Some(deadline))
+            .map_err(|running| Error::Timeout { group: self.id.clone(), running })
+    }
+
+    /// `ThreadGroup::drain` returns an iterator which joins the
+    /// threads lazily in blocking fashion as it is advanced, yielding
+    /// the result of each thread in spawn order
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain { group: self }
+    }
+
+    /// `ThreadGroup::results_with_stats` waits for the all threads to
+    /// join in blocking fashion, returning all their results at once
+    /// along with their [`TaskStats`]
+    pub fn results_with_stats(&mut self) -> Vec<(Result<T>, TaskStats)> {
+        let mut val = Vec::<(Result<T>, TaskStats)>::new();
+        while let Some((task, handle)) = self.handles.pop_front() {
+            let end = self.join_handle(task.clone(), handle);
+            let stats = self.records.get(&task).and_then(|(clock, _)| clock.stats());
+            val.push((end, stats.unwrap_or_default()));
+        }
+        self.wait_children();
+        val
+    }
+
+    /// `ThreadGroup::stats` returns the [`Stats`] of every thread
+    /// spawned in the group which finished running, joined or not
+    pub fn stats(&self) -> Stats {
+        let tasks = self
+            .records
+            .iter()
+            .filter_map(|(task, (clock, _))| Some((task.clone(), clock.stats()?)))
+            .collect::<BTreeMap<TaskId, TaskStats>>();
+        let first = self.records.values().map(|(clock, _)| clock.spawned).min();
+        let last = self.records.values().filter_map(|(clock, _)| clock.finished.get()).max();
+        Stats {
+            elapsed: match (first, last) {
+                (Some(first), Some(last)) => last.saturating_duration_since(first),
+                _ => Duration::ZERO,
+            },
+            duration: Summary::new(tasks.values().map(|t| t.duration).collect()),
+            queue_delay: Summary::new(tasks.values().map(|t| t.queue_delay).collect()),
+            tasks,
+        }
+    }
+
+    /// `ThreadGroup::results_by_id` waits for the all threads to join
+    /// in blocking fashion, returning all their results at once as a
+    /// [`BTreeMap<TaskId, Result<T>>`]
+    pub fn results_by_id(&mut self) -> BTreeMap<TaskId, Result<T>> {
+        let mut val = BTreeMap::<TaskId, Result<T>>::new();
+        while let Some((task, handle)) = self.handles.pop_front() {
+            val.insert(task.clone(), self.join_handle(task, handle));
+        }
+        self.wait_children();
+        val
+    }
+
+    /// `ThreadGroup::results_timeout` waits up to `timeout` for the
+    /// all threads to finish, returning all their results at once as
+    /// a [`Vec<Result<T>>`] or [`Error::Timeout`] without joining any
+    /// thread if some are still running by then
+    pub fn results_timeout(&mut self, timeout: Duration) -> Result<Vec<Result<T>>> {
+        self.results_deadline(Instant::now() + timeout)
+    }
+
+    /// `ThreadGroup::results_deadline` waits until `deadline` for the
+    /// all threads to finish, returning all their results at once as
+    /// a [`Vec<Result<T>>`] or [`Error::Timeout`] without joining any
+    /// thread if some are still running by then
+    pub fn results_deadline(&mut self, deadline: Instant) -> Result<Vec<Result<T>>> {
+        self.wait_deadline(self.handles.len(), deadline)?;
+        self.wait_children_deadline(deadline)?;
+        Ok(self.results())
+    }
+
+    /// `ThreadGroup::results_in_completion_order` waits for the all
+    /// threads to join in blocking fashion, returning all their
+    /// results at once as a [`Vec<Result<T>>`] ordered by the time
+    /// each thread finished
+    pub fn results_in_completion_order(&mut self) -> Vec<Result<T>> {
+        let mut val = Vec::<Result<T>>::new();
+        while !self.handles.is_empty() {
+            val.push(self.join_any());
+        }
+        self.wait_children();
+        val
+    }
+
+    /// `ThreadGroup::as_far_as_ok` waits for the all threads to join in
+    /// blocking fashion, returning all the OK results at once as a [`Vec<T>`] but ignoring all errors.
+    pub fn as_far_as_ok(&mut self) -> Vec<T> {
+        let mut val = Vec::<T>::new();
+        while !self.handles.is_empty() {
+            if let Ok(g) = self.join() {
+                val.push(g)
+            }
+        }
+        self.wait_children();
+        val
+    }
+
+    /// `ThreadGroup::as_far_as_ok_timeout` waits up to `timeout` for
+    /// the all threads to finish, returning all the OK results at once
+    /// as a [`Vec<T>`] or [`Error::Timeout`] without joining any thread
+    /// if some are still running by then
+    pub fn as_far_as_ok_timeout(&mut self, timeout: Duration) -> Result<Vec<T>> {
+        self.as_far_as_ok_deadline(Instant::now() + timeout)
+    }
+
+    /// `ThreadGroup::as_far_as_ok_deadline` waits until `deadline` for
+    /// the all threads to finish, returning all the OK results at once
+    /// as a [`Vec<T>`] or [`Error::Timeout`] without joining any thread
+    /// if some are still running by then
+    pub fn as_far_as_ok_deadline(&mut self, deadline: Instant) -> Result<Vec<T>> {
+        self.wait_deadline(self.handles.len(), deadline)?;
+        self.wait_children_deadline(deadline)?;
+        Ok(self.as_far_as_ok())
+    }
+
+    /// `ThreadGroup::all_ok` waits for the all threads to join in
+    /// blocking fashion, returning all the OK results at once as a [`Vec<T>`] if there are no errors.
+    ///
+    /// The remaining threads are cancelled upon the first error when
+    /// [`ThreadGroup::set_cancel_on_failure`] is enabled.
+    pub fn all_ok(&mut self) -> Result<Vec<T>> {
+        let mut val = Vec::<T>::new();
+        while !self.handles.is_empty() {
+            match self.join() {
+                Ok(t) => val.push(t),
+                Err(e) => {
+                    if self.cancel_on_failure {
+                        self.cancel();
+                    }
+                    return Err(e);
+                },
+            }
+        }
+        self.wait_children();
+        Ok(val)
+    }
+
+    /// `ThreadGroup::all_ok_fail_fast` waits for the all threads to
+    /// join in blocking fashion as they finish, returning all the OK
+    /// results at once as a [`Vec<T>`] in completion order if there
+    /// are no errors.
+    ///
+    /// Returns [`Error::Aborted`] as soon as any thread fails,
+    /// regardless of the order in which threads were spawned, leaving
+    /// the remaining threads in the group.
+    pub fn all_ok_fail_fast(&mut self) -> Result<Vec<T>> {
+        let mut val = Vec::<T>::new();
+        while !self.handles.is_empty() {
+            match self.join_any() {
+                Ok(t) => val.push(t),
+                Err(e) => {
+                    if self.cancel_on_failure {
+                        self.cancel();
+                    }
+                    return Err(Error::Aborted { cause: Box::new(e), running: self.running() });
+                },
+            }
+        }
+        self.wait_children();
+        Ok(val)
+    }
+
+    /// `ThreadGroup::all_ok_timeout` waits up to `timeout` for the all
+    /// threads to finish, returning all the OK results at once as a
+    /// [`Vec<T>`] if there are no errors or [`Error::Timeout`] without
+    /// joining any thread if some are still running by then
+    pub fn all_ok_timeout(&mut self, timeout: Duration) -> Result<Vec<T>> {
+        self.all_ok_deadline(Instant::now() + timeout)
+    }
+
+    /// `ThreadGroup::all_ok_deadline` waits until `deadline` for the
+    /// all threads to finish, returning all the OK results at once as
+    /// a [`Vec<T>`] if there are no errors or [`Error::Timeout`]
+    /// without joining any thread if some are still running by then
+    pub fn all_ok_deadline(&mut self, deadline: Instant) -> Result<Vec<T>> {
+        self.wait_deadline(self.handles.len(), deadline)?;
+        self.wait_children_deadline(deadline)?;
+        self.all_ok()
+    }
+
+    /// `ThreadGroup::errors` returns a [`BTreeMap<String, Error>`] of errors whose keys are thread ids that panicked.
+    ///
+    /// The errors of subgroups created with [`ThreadGroup::child`]
+    /// are included as soon as they are joined.
+    pub fn errors(&self) -> BTreeMap<String, Error> {
+        let mut errors = self.errors.clone();
+        for (id, e) in self.family.errors.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            insert_error(&mut errors, id.clone(), e.clone());
+        }
+        errors
+    }
+
+    /// `ThreadGroup::take_panic` removes and returns the raw panic
+    /// payload of the thread whose id is `id` (as in the keys of
+    /// [`ThreadGroup::errors`]) so that it can be downcast or passed
+    /// to [`std::panic::resume_unwind`]
+    pub fn take_panic(&mut self, id: &str) -> Option<Box<dyn Any + Send>> {
+        self.panics.remove(id)
+    }
+
+    /// `ThreadGroup::take_panics` removes and returns all raw panic
+    /// payloads as a [`BTreeMap<String, Box<dyn Any + Send>>`] whose
+    /// keys are thread ids that panicked
+    pub fn take_panics(&mut self) -> BTreeMap<String, Box<dyn Any + Send>> {
+        std::mem::take(&mut self.panics)
+    }
+}
+
+impl<T> ThreadGroup<T> {
+    fn join_on_drop<R>(&self, task: TaskId, handle: JoinHandle<R>) {
+        let id = thread_id(handle.thread());
+        if let Err(payload) = handle.join() {
+            let e = join_error(&task, &*payload);
+            self.family.bubble(&id, &e);
+            match &self.drop_handler {
+                Some(handler) => handler(&id, e),
+                None => eprintln!("{}", e),
+            }
+        }
+    }
+}
+
+impl<T> Drop for ThreadGroup<T> {
+    fn drop(&mut self) {
+        self.channel.take();
+        if let Some(gate) = &self.family.gate {
+            gate.release();
+        }
+        if self.handles.is_empty() && self.producers.is_empty() {
+            return;
+        }
+        let unjoined = || {
+            let names = self.handles.iter().map(|(task, _)| task.name.as_str());
+            let names = names
+                .chain(self.producers.iter().map(|(task, _)| task.name.as_str()))
+                .collect::<Vec<_>>();
+            format!("{} dropped with unjoined threads: {}", &self, names.join(", "))
+        };
+        match self.drop_policy {
+            DropPolicy::Detach => {},
+            DropPolicy::LogIfUnjoined => eprintln!("{}", unjoined()),
+            DropPolicy::PanicIfUnjoined if !std::thread::panicking() => panic!("{}", unjoined()),
+            DropPolicy::PanicIfUnjoined => {},
+            DropPolicy::JoinOnDrop => {
+                while let Some((task, handle)) = self.handles.pop_front() {
+                    self.join_on_drop(task, handle);
+                }
+                while let Some((task, handle)) = self.producers.pop_front() {
+                    self.join_on_drop(task, handle);
+                }
+            },
+        }
+    }
+}
+
+impl<T> std::fmt::Display for ThreadGroup<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}::ThreadGroup {}", module_path!(), &self.id)
+    }
+}
+
+impl<T: Send + Sync + 'static> Default for ThreadGroup<T> {
+    fn default() -> ThreadGroup<T> {
+        Self::new()
+    }
+}
+
+/// `Drain` is the iterator returned by [`ThreadGroup::drain`]
+pub struct Drain<'a, T> {
+    group: &'a mut ThreadGroup<T>,
+}
+
+impl<T: Send + Sync + 'static> Iterator for Drain<'_, T> {
+    type Item = Result<T>;
 
-use std::collections::{BTreeMap, VecDeque};
-use std::fmt::Display;
-use std::thread::{Builder, JoinHandle, Thread};
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.group.handles.is_empty() {
+            self.group.wait_children();
+            None
+        } else {
+            Some(self.group.join())
+        }
+    }
 
-/// `thread_id` returns a deterministic name for instances of [`std::thread::Thread`].
-pub fn thread_id(thread: &Thread) -> String {
-    format!(
-        "{}:{}",
-        std::process::id(),
-        thread
-            .name()
-            .map(|a| a.to_string())
-            .unwrap_or_else(|| format!("{:#?}", thread.id()))
-            .to_string()
-    )
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.group.handles.len(), Some(self.group.handles.len()))
+    }
 }
 
-/// `ThreadGroup` is allows spawning several threads and waiting for
-/// their completion through the specialized methods.
-pub struct ThreadGroup<T> {
+impl<T: Send + Sync + 'static> ExactSizeIterator for Drain<'_, T> {}
+
+/// `IntoIter` is the iterator returned by [`ThreadGroup::into_iter`]
+pub struct IntoIter<T> {
+    group: ThreadGroup<T>,
+}
+
+impl<T: Send + Sync + 'static> Iterator for IntoIter<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.group.drain().next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.group.handles.len(), Some(self.group.handles.len()))
+    }
+}
+
+impl<T: Send + Sync + 'static> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: Send + Sync + 'static> IntoIterator for ThreadGroup<T> {
+    type Item = Result<T>;
+    type IntoIter = IntoIter<T>;
+
+    /// `ThreadGroup::into_iter` returns an iterator which joins the
+    /// threads lazily in blocking fashion as it is advanced, yielding
+    /// the result of each thread in spawn order
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { group: self }
+    }
+}
+
+/// `Receive` is the iterator returned by [`ThreadGroup::receiver`]
+pub struct Receive<'a, T: Send + Sync + 'static> {
+    group: &'a mut ThreadGroup<T>,
+    receiver: Option<Receiver<T>>,
+}
+
+impl<T: Send + Sync + 'static> Iterator for Receive<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if let Some(item) = self.receiver.as_ref().and_then(|receiver| receiver.recv().ok()) {
+            return Some(item);
+        }
+        self.receiver = None;
+        while let Some((task, handle)) = self.group.producers.pop_front() {
+            let _ = self.group.join_handle(task, handle);
+        }
+        None
+    }
+}
+
+impl<'a, T: Send + Sync + 'static> IntoIterator for &'a mut ThreadGroup<T> {
+    type Item = Result<T>;
+    type IntoIter = Drain<'a, T>;
+
+    fn into_iter(self) -> Drain<'a, T> {
+        self.drain()
+    }
+}
+
+/// Spawns a thread for each closure, recording spawn failures in
+/// [`ThreadGroup::errors`]
+impl<T: Send + Sync + 'static, F: FnOnce() -> T + Send + 'static> Extend<F> for ThreadGroup<T> {
+    fn extend<I: IntoIterator<Item = F>>(&mut self, iter: I) {
+        for func in iter {
+            if let Err(e) = self.spawn(func) {
+                self.record_spawn_error(&e);
+            }
+        }
+    }
+}
+
+/// Creates a [`ThreadGroup`] with [`ThreadGroup::new`] and spawns a
+/// thread for each closure, recording spawn failures in
+/// [`ThreadGroup::errors`]
+impl<T: Send + Sync + 'static, F: FnOnce() -> T + Send + 'static> FromIterator<F>
+    for ThreadGroup<T>
+{
+    fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> ThreadGroup<T> {
+        let mut group = ThreadGroup::new();
+        group.extend(iter);
+        group
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: Send + Sync + 'static> ThreadGroup<T> {
+    /// `ThreadGroup::join_all` returns a [`std::future::Future`] which
+    /// resolves once all threads joined, to all their results as a
+    /// [`Vec<Result<T>>`] ordered by the time each thread finished.
+    ///
+    /// It only resolves once the threads of subgroups created with
+    /// [`ThreadGroup::child`] finished running as well.
+    pub fn join_all(&mut self) -> JoinAll<'_, T> {
+        JoinAll { group: self, results: Vec::new() }
+    }
+}
+
+/// `ThreadGroup` is a [`futures_core::Stream`] of the results of its
+/// threads in completion order, ending when all threads joined and
+/// the threads of its subgroups finished running.
+#[cfg(feature = "async")]
+impl<T: Send + Sync + 'static> futures_core::Stream for ThreadGroup<T> {
+    type Item = Result<T>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Result<T>>> {
+        let group = self.get_mut();
+        group.release();
+        if group.handles.is_empty() {
+            if group.family.register(cx.waker()) {
+                return std::task::Poll::Ready(None);
+            }
+            return std::task::Poll::Pending;
+        }
+        let completions = group.completions.clone();
+        let mut finished = completions.lock();
+        match group.next_completed(&mut finished) {
+            Some(position) => {
+                drop(finished);
+                let (task, handle) =
+                    group.handles.remove(position).expect("completed thread handle");
+                std::task::Poll::Ready(Some(group.join_handle(task, handle)))
+            },
+            None => {
+                completions.register(cx.waker());
+                std::task::Poll::Pending
+            },
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.handles.len(), Some(self.handles.len()))
+    }
+}
+
+/// `JoinAll` is the [`std::future::Future`] returned by `ThreadGroup::join_all`
+#[cfg(feature = "async")]
+pub struct JoinAll<'a, T> {
+    group: &'a mut ThreadGroup<T>,
+    results: Vec<Result<T>>,
+}
+
+#[cfg(feature = "async")]
+impl<T> Unpin for JoinAll<'_, T> {}
+
+#[cfg(feature = "async")]
+impl<T: Send + Sync + 'static> std::future::Future for JoinAll<'_, T> {
+    type Output = Vec<Result<T>>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Vec<Result<T>>> {
+        let this = self.get_mut();
+        loop {
+            match futures_core::Stream::poll_next(std::pin::Pin::new(&mut *this.group), cx) {
+                std::task::Poll::Ready(Some(end)) => this.results.push(end),
+                std::task::Poll::Ready(None) => {
+                    return std::task::Poll::Ready(std::mem::take(&mut this.results))
+                },
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            }
+        }
+    }
+}
+
+/// `TryThreadGroup` is the counterpart of [`ThreadGroup`] for
+/// closures returning [`std::result::Result<U, E>`] whose errors are
+/// flattened into the group's error reporting as [`Error::TaskError`],
+/// i.e.: counted in [`TryThreadGroup::errors`] and failing
+/// [`TryThreadGroup::all_ok`] just like panics.
+///
+/// `E` is any type convertible into a boxed [`std::error::Error`],
+/// such as concrete error types or `Box<dyn Error + Send + Sync>`.
+pub struct TryThreadGroup<U, E> {
+    group: ThreadGroup<std::result::Result<U, E>>,
+}
+impl<U, E> TryThreadGroup<U, E>
+where
+    U: Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + Send + Sync + 'static,
+{
+    /// `TryThreadGroup::new` creates a new thread group
+    pub fn new() -> TryThreadGroup<U, E> {
+        TryThreadGroup { group: ThreadGroup::new() }
+    }
+
+    /// `TryThreadGroup::with_id` creates a new thread group with a specific id ([`String`])
+    pub fn with_id(id: String) -> TryThreadGroup<U, E> {
+        TryThreadGroup { group: ThreadGroup::with_id(id) }
+    }
+
+    /// `TryThreadGroup::spawn` spawns a thread, returning its [`TaskId`]
+    pub fn spawn<F>(&mut self, func: F) -> Result<TaskId>
+    where
+        F: FnOnce() -> std::result::Result<U, E> + Send + 'static,
+    {
+        self.group.spawn(func)
+    }
+
+    /// `TryThreadGroup::spawn_with` spawns a thread with specific
+    /// [`ThreadOptions`], returning its [`TaskId`]
+    pub fn spawn_with<F>(&mut self, options: ThreadOptions, func: F) -> Result<TaskId>
+    where
+        F: FnOnce() -> std::result::Result<U, E> + Send + 'static,
+    {
+        self.group.spawn_with(options, func)
+    }
+
+    /// `TryThreadGroup::join` waits for the first thread to join in
+    /// blocking fashion, returning the result of that threads
+    /// [`FnOnce`]
+    pub fn join(&mut self) -> Result<U> {
+        let task = self.group.handles.front().map(|(task, _)| task.clone());
+        let end = self.group.join()?;
+        self.flatten(task.expect("joined thread task"), end)
+    }
+
+    /// `TryThreadGroup::join_id` waits for the thread identified by
+    /// `id` to join in blocking fashion, returning the result of that
+    /// threads [`FnOnce`]
+    pub fn join_id(&mut self, id: &TaskId) -> Result<U> {
+        let end = self.group.join_id(id)?;
+        self.flatten(id.clone(), end)
+    }
+
+    /// `TryThreadGroup::join_any` waits for whichever thread finishes
+    /// first in blocking fashion, returning the result of that
+    /// threads [`FnOnce`]
+    pub fn join_any(&mut self) -> Result<U> {
+        let (task, end) = self.group.join_next_completed()?;
+        self.flatten(task, end)
+    }
+
+    fn flatten(&mut self, task: TaskId, end: std::result::Result<U, E>) -> Result<U> {
+        end.map_err(|e| {
+            let id = format!("{}:{}", std::process::id(), &task.name);
+            let source = Arc::from(e.into());
+            let e = Error::TaskError { thread: task.name, index: task.index, source };
+            self.group.record_error(id, e.clone());
+            self.group.failed += 1;
+            e
+        })
+    }
+
+    /// `TryThreadGroup::results` waits for the all threads to join in
+    /// blocking fashion, returning all their results at once as a
+    /// [`Vec<Result<U>>`]
+    pub fn results(&mut self) -> Vec<Result<U>> {
+        let mut val = Vec::<Result<U>>::new();
+        while !self.group.handles.is_empty() {
+            val.push(self.join());
+        }
+        self.group.wait_children();
+        val
+    }
+
+    /// `TryThreadGroup::as_far_as_ok` waits for the all threads to join
+    /// in blocking fashion, returning all the OK results at once as a
+    /// [`Vec<U>`] but ignoring all errors.
+    pub fn as_far_as_ok(&mut self) -> Vec<U> {
+        let mut val = Vec::<U>::new();
+        while !self.group.handles.is_empty() {
+            if let Ok(g) = self.join() {
+                val.push(g)
+            }
+        }
+        self.group.wait_children();
+        val
+    }
+
+    /// `TryThreadGroup::all_ok` waits for the all threads to join in
+    /// blocking fashion, returning all the OK results at once as a
+    /// [`Vec<U>`] if no thread panicked or returned an error.
+    ///
+    /// The remaining threads are cancelled upon the first error when
+    /// [`TryThreadGroup::set_cancel_on_failure`] is enabled.
+    pub fn all_ok(&mut self) -> Result<Vec<U>> {
+        let mut val = Vec::<U>::new();
+        while !self.group.handles.is_empty() {
+            match self.join() {
+                Ok(u) => val.push(u),
+                Err(e) => {
+                    if self.group.cancel_on_failure {
+                        self.group.cancel();
+                    }
+                    return Err(e);
+                },
+            }
+        }
+        self.group.wait_children();
+        Ok(val)
+    }
+
+    /// `TryThreadGroup::set_cancel_on_failure` see [`ThreadGroup::set_cancel_on_failure`]
+    pub fn set_cancel_on_failure(&mut self, cancel_on_failure: bool) {
+        self.group.set_cancel_on_failure(cancel_on_failure);
+    }
+
+    /// `TryThreadGroup::cancel` see [`ThreadGroup::cancel`]
+    pub fn cancel(&self) {
+        self.group.cancel();
+    }
+
+    /// `TryThreadGroup::token` see [`ThreadGroup::token`]
+    pub fn token(&self) -> CancellationToken {
+        self.group.token()
+    }
+
+    /// `TryThreadGroup::errors` returns a [`BTreeMap<String, Error>`] of
+    /// errors whose keys are thread ids that panicked or returned an error.
+    pub fn errors(&self) -> BTreeMap<String, Error> {
+        self.group.errors()
+    }
+
+    /// `TryThreadGroup::failed` returns the number of joined threads
+    /// that panicked or returned an error
+    pub fn failed(&self) -> usize {
+        self.group.failed()
+    }
+}
+
+impl<U, E> From<ThreadGroup<std::result::Result<U, E>>> for TryThreadGroup<U, E> {
+    fn from(group: ThreadGroup<std::result::Result<U, E>>) -> TryThreadGroup<U, E> {
+        TryThreadGroup { group }
+    }
+}
+
+impl<U, E> Default for TryThreadGroup<U, E>
+where
+    U: Send + Sync + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + Send + Sync + 'static,
+{
+    fn default() -> TryThreadGroup<U, E> {
+        Self::new()
+    }
+}
+
+impl<U, E> std::fmt::Display for TryThreadGroup<U, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}::TryThreadGroup {}", module_path!(), &self.group.id)
+    }
+}
+
+type Job<T> = (usize, Box<dyn FnOnce() -> T + Send>);
+
+/// `ThreadPoolGroup` is the counterpart of [`ThreadGroup`] which runs
+/// every spawned closure on at most `max_threads` reusable worker
+/// threads, queueing closures until a worker becomes available.
+///
+/// Dropping the group discards the queued closures which no worker
+/// picked up yet and waits for the running ones to complete.
+pub struct ThreadPoolGroup<T> {
     id: String,
-    handles: VecDeque<JoinHandle<T>>,
-    count: usize,
+    max_threads: usize,
+    workers: Vec<JoinHandle<()>>,
+    jobs: Option<Sender<Job<T>>>,
+    queue: Arc<Mutex<Receiver<Job<T>>>>,
+    done: Receiver<(usize, std::thread::Result<T>)>,
+    done_sender: Sender<(usize, std::thread::Result<T>)>,
+    pending: VecDeque<usize>,
+    finished: BTreeMap<usize, std::thread::Result<T>>,
+    spawned: usize,
+    joined: usize,
+    failed: usize,
     errors: BTreeMap<String, Error>,
+    panics: BTreeMap<String, Box<dyn Any + Send>>,
 }
-impl<T: Send + Sync + 'static> ThreadGroup<T> {
-    /// `ThreadGroup::new` creates a new thread group
-    pub fn new() -> ThreadGroup<T> {
-        ThreadGroup::with_id(thread_id(&std::thread::current()))
+impl<T: Send + Sync + 'static> ThreadPoolGroup<T> {
+    /// `ThreadPoolGroup::new` creates a new thread pool group running
+    /// at most `max_threads` threads at once
+    pub fn new(max_threads: usize) -> ThreadPoolGroup<T> {
+        ThreadPoolGroup::with_id(thread_id(&std::thread::current()), max_threads)
     }
 
-    /// `ThreadGroup::with_id` creates a new thread group with a specific id ([`String`])
-    pub fn with_id(id: String) -> ThreadGroup<T> {
-        ThreadGroup {
+    /// `ThreadPoolGroup::with_id` creates a new thread pool group with
+    /// a specific id ([`String`]) running at most `max_threads`
+    /// threads at once
+    pub fn with_id(id: String, max_threads: usize) -> ThreadPoolGroup<T> {
+        let (jobs, queue) = channel();
+        let (done_sender, done) = channel();
+        ThreadPoolGroup {
             id,
-            handles: VecDeque::new(),
+            max_threads: max_threads.max(1),
+            workers: Vec::new(),
+            jobs: Some(jobs),
+            queue: Arc::new(Mutex::new(queue)),
+            done,
+            done_sender,
+            pending: VecDeque::new(),
+            finished: BTreeMap::new(),
+            spawned: 0,
+            joined: 0,
+            failed: 0,
             errors: BTreeMap::new(),
-            count: 0,
+            panics: BTreeMap::new(),
         }
     }
 
-    /// `ThreadGroup::spawn` spawns a thread
-    pub fn spawn<F: FnOnce() -> T + Send + 'static>(&mut self, func: F) -> Result<()> {
-        self.count += 1;
-        let name = format!("{}:{}", &self.id, self.count);
-        self.handles.push_back(
-            Builder::new().name(name.clone()).spawn(func).map_err(|e| {
-                Error::ThreadSpawnError(format!("spawning thread {}: {:#?}", name, e))
-            })?,
+    /// `ThreadPoolGroup::max_threads` returns the maximum number of
+    /// worker threads of the group
+    pub fn max_threads(&self) -> usize {
+        self.max_threads
+    }
+
+    /// `ThreadPoolGroup::spawn` queues a closure to run in one of the
+    /// worker threads, spawning a new worker if less than
+    /// `max_threads` are running, and returns its [`TaskId`].
+    ///
+    /// Failing to spawn a new worker only results in an error when
+    /// there are no workers to run the closure at all.
+    pub fn spawn<F: FnOnce() -> T + Send + 'static>(&mut self, func: F) -> Result<TaskId> {
+        if self.workers.len() < self.max_threads {
+            if let Err(e) = self.spawn_worker() {
+                if self.workers.is_empty() {
+                    return Err(e);
+                }
+            }
+        }
+        let index = self.spawned + 1;
+        self.jobs
+            .as_ref()
+            .expect("job queue")
+            .send((index, Box::new(func)))
+            .map_err(|_| Error::ThreadGroupError(format!("no workers in group {}", &self)))?;
+        self.spawned = index;
+        self.pending.push_back(index);
+        Ok(TaskId { index, name: format!("{}:{}", &self.id, index) })
+    }
+
+    fn spawn_worker(&mut self) -> Result<()> {
+        let name = format!("{}:worker:{}", &self.id, self.workers.len() + 1);
+        let queue = self.queue.clone();
+        let done = self.done_sender.clone();
+        self.workers.push(
+            Builder::new()
+                .name(name.clone())
+                .spawn(move || loop {
+                    let job = queue.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                    match job {
+                        Ok((index, func)) => {
+                            let end = std::panic::catch_unwind(AssertUnwindSafe(func));
+                            let _ = done.send((index, end));
+                        },
+                        Err(_) => break,
+                    }
+                })
+                .map_err(|e| spawn_error(name, e))?,
         );
         Ok(())
     }
 
-    /// `ThreadGroup::join` waits for the first thread to join in
+    /// `ThreadPoolGroup::join` waits for the first queued closure to
+    /// complete in blocking fashion, returning its result
+    pub fn join(&mut self) -> Result<T> {
+        let index = self
+            .pending
+            .pop_front()
+            .ok_or(Error::ThreadGroupError(format!("no threads in group {}", &self)))?;
+
+        let end = loop {
+            if let Some(end) = self.finished.remove(&index) {
+                break end;
+            }
+            let (done, end) = self.done.recv().map_err(|_| {
+                Error::ThreadGroupError(format!("no workers in group {}", &self))
+            })?;
+            self.finished.insert(done, end);
+        };
+        let task = TaskId { index, name: format!("{}:{}", &self.id, index) };
+        let id = format!("{}:{}", std::process::id(), &task.name);
+        self.joined += 1;
+        match end {
+            Ok(t) => Ok(t),
+            Err(payload) => {
+                let e = join_error(&task, &*payload);
+                let key = insert_error(&mut self.errors, id, e.clone());
+                self.panics.insert(key, payload);
+                self.failed += 1;
+                Err(e)
+            },
+        }
+    }
+
+    /// `ThreadPoolGroup::spawned` returns the number of closures ever
+    /// queued in the group
+    pub fn spawned(&self) -> usize {
+        self.spawned
+    }
+
+    /// `ThreadPoolGroup::live` returns the number of closures in the
+    /// group whose results were not joined yet
+    pub fn live(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// `ThreadPoolGroup::joined` returns the number of closures joined
+    /// so far, including the ones that panicked
+    pub fn joined(&self) -> usize {
+        self.joined
+    }
+
+    /// `ThreadPoolGroup::failed` returns the number of joined closures
+    /// that panicked
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    /// `ThreadPoolGroup::results` waits for the all queued closures to
+    /// complete in blocking fashion, returning all their results at
+    /// once as a [`Vec<Result<T>>`]
+    pub fn results(&mut self) -> Vec<Result<T>> {
+        let mut val = Vec::<Result<T>>::new();
+        while !self.pending.is_empty() {
+            val.push(self.join());
+        }
+        val
+    }
+
+    /// `ThreadPoolGroup::as_far_as_ok` waits for the all queued
+    /// closures to complete in blocking fashion, returning all the OK
+    /// results at once as a [`Vec<T>`] but ignoring all errors.
+    pub fn as_far_as_ok(&mut self) -> Vec<T> {
+        let mut val = Vec::<T>::new();
+        while !self.pending.is_empty() {
+            if let Ok(g) = self.join() {
+                val.push(g)
+            }
+        }
+        val
+    }
+
+    /// `ThreadPoolGroup::all_ok` waits for the all queued closures to
+    /// complete in blocking fashion, returning all the OK results at
+    /// once as a [`Vec<T>`] if there are no errors.
+    pub fn all_ok(&mut self) -> Result<Vec<T>> {
+        let mut val = Vec::<T>::new();
+        while !self.pending.is_empty() {
+            val.push(self.join()?);
+        }
+        Ok(val)
+    }
+
+    /// `ThreadPoolGroup::errors` returns a [`BTreeMap<String, Error>`]
+    /// of errors whose keys are the ids of closures that panicked.
+    pub fn errors(&self) -> BTreeMap<String, Error> {
+        self.errors.clone()
+    }
+
+    /// `ThreadPoolGroup::take_panics` removes and returns all raw panic
+    /// payloads as a [`BTreeMap<String, Box<dyn Any + Send>>`] whose
+    /// keys are the ids of closures that panicked
+    pub fn take_panics(&mut self) -> BTreeMap<String, Box<dyn Any + Send>> {
+        std::mem::take(&mut self.panics)
+    }
+}
+
+impl<T> Drop for ThreadPoolGroup<T> {
+    fn drop(&mut self) {
+        self.jobs.take();
+        let queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        while queue.try_recv().is_ok() {}
+        drop(queue);
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<T> std::fmt::Display for ThreadPoolGroup<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}::ThreadPoolGroup {}", module_path!(), &self.id)
+    }
+}
+
+/// `scope` creates a [`ScopedThreadGroup`] within
+/// [`std::thread::scope`], whose threads may borrow non-`'static`
+/// data. Threads left in the group when `f` returns are joined before
+/// the scope ends.
+pub fn scope<'env, T, F, R>(f: F) -> R
+where
+    T: Send + 'env,
+    F: for<'scope> FnOnce(&mut ScopedThreadGroup<'scope, 'env, T>) -> R,
+{
+    scope_with_id(thread_id(&std::thread::current()), f)
+}
+
+/// `scope_with_id` creates a [`ScopedThreadGroup`] with a specific id
+/// ([`String`]) within [`std::thread::scope`]
+pub fn scope_with_id<'env, T, F, R>(id: String, f: F) -> R
+where
+    T: Send + 'env,
+    F: for<'scope> FnOnce(&mut ScopedThreadGroup<'scope, 'env, T>) -> R,
+{
+    std::thread::scope(|scope| {
+        let mut group = ScopedThreadGroup {
+            id,
+            scope,
+            handles: VecDeque::new(),
+            errors: BTreeMap::new(),
+            spawned: 0,
+            joined: 0,
+            failed: 0,
+        };
+        let end = f(&mut group);
+        group.results();
+        end
+    })
+}
+
+/// `ScopedThreadGroup` is the counterpart of [`ThreadGroup`] for
+/// threads spawned within a [`std::thread::Scope`], see [`scope`].
+pub struct ScopedThreadGroup<'scope, 'env: 'scope, T> {
+    id: String,
+    scope: &'scope Scope<'scope, 'env>,
+    handles: VecDeque<(TaskId, ScopedJoinHandle<'scope, T>)>,
+    spawned: usize,
+    joined: usize,
+    failed: usize,
+    errors: BTreeMap<String, Error>,
+}
+impl<'scope, 'env, T: Send + 'scope> ScopedThreadGroup<'scope, 'env, T> {
+    /// `ScopedThreadGroup::spawn` spawns a scoped thread, returning its [`TaskId`]
+    pub fn spawn<F: FnOnce() -> T + Send + 'scope>(&mut self, func: F) -> Result<TaskId> {
+        let index = self.spawned + 1;
+        let name = format!("{}:{}", &self.id, index);
+        let handle = Builder::new()
+            .name(name.clone())
+            .spawn_scoped(self.scope, func)
+            .map_err(|e| spawn_error(name.clone(), e))?;
+        self.spawned = index;
+        let task = TaskId { index, name };
+        self.handles.push_back((task.clone(), handle));
+        Ok(task)
+    }
+
+    /// `ScopedThreadGroup::join` waits for the first thread to join in
     /// blocking fashion, returning the result of that threads
     /// [`FnOnce`]
     pub fn join(&mut self) -> Result<T> {
-        let handle = self
+        let (task, handle) = self
             .handles
             .pop_front()
             .ok_or(Error::ThreadGroupError(format!("no threads in group {}", &self)))?;
 
-        let id = thread_id(&handle.thread());
+        let id = thread_id(handle.thread());
 
         let end = match handle.join() {
             Ok(t) => Ok(t),
-            Err(e) => {
-                let e = Error::ThreadJoinError(format!("joining thread {}: {:#?}", id, e));
-                self.errors.insert(id, e.clone());
+            Err(payload) => {
+                let e = join_error(&task, &*payload);
+                insert_error(&mut self.errors, id, e.clone());
+                self.failed += 1;
                 Err(e)
             },
         };
-        self.count -= 1;
+        self.joined += 1;
         end
     }
 
-    /// `ThreadGroup::results` waits for the all threads to join in
-    /// blocking fashion, returning all their results at once as a [`Vec<Result<T>>`]
+    /// `ScopedThreadGroup::results` waits for the all threads to join
+    /// in blocking fashion, returning all their results at once as a
+    /// [`Vec<Result<T>>`]
     pub fn results(&mut self) -> Vec<Result<T>> {
         let mut val = Vec::<Result<T>>::new();
         while !self.handles.is_empty() {
@@ -90,8 +2620,9 @@ impl<T: Send + Sync + 'static> ThreadGroup<T> {
         val
     }
 
-    /// `ThreadGroup::as_far_as_ok` waits for the all threads to join in
-    /// blocking fashion, returning all the OK results at once as a [`Vec<T>`] but ignoring all errors.
+    /// `ScopedThreadGroup::as_far_as_ok` waits for the all threads to
+    /// join in blocking fashion, returning all the OK results at once
+    /// as a [`Vec<T>`] but ignoring all errors.
     pub fn as_far_as_ok(&mut self) -> Vec<T> {
         let mut val = Vec::<T>::new();
         while !self.handles.is_empty() {
@@ -102,8 +2633,9 @@ impl<T: Send + Sync + 'static> ThreadGroup<T> {
         val
     }
 
-    /// `ThreadGroup::all_ok` waits for the all threads to join in
-    /// blocking fashion, returning all the OK results at once as a [`Vec<T>`] if there are no errors.
+    /// `ScopedThreadGroup::all_ok` waits for the all threads to join in
+    /// blocking fashion, returning all the OK results at once as a
+    /// [`Vec<T>`] if there are no errors.
     pub fn all_ok(&mut self) -> Result<Vec<T>> {
         let mut val = Vec::<T>::new();
         while !self.handles.is_empty() {
@@ -112,29 +2644,51 @@ impl<T: Send + Sync + 'static> ThreadGroup<T> {
         Ok(val)
     }
 
-    /// `ThreadGroup::errors` returns a [`BTreeMap<String, Error>`] of errors whose keys are thread ids that panicked.
+    /// `ScopedThreadGroup::spawned` returns the number of threads ever
+    /// spawned in the group
+    pub fn spawned(&self) -> usize {
+        self.spawned
+    }
+
+    /// `ScopedThreadGroup::live` returns the number of threads in the
+    /// group which were not joined yet
+    pub fn live(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// `ScopedThreadGroup::joined` returns the number of threads joined
+    /// so far, including the ones that panicked
+    pub fn joined(&self) -> usize {
+        self.joined
+    }
+
+    /// `ScopedThreadGroup::failed` returns the number of joined threads
+    /// that panicked
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    /// `ScopedThreadGroup::errors` returns a [`BTreeMap<String, Error>`]
+    /// of errors whose keys are thread ids that panicked.
     pub fn errors(&self) -> BTreeMap<String, Error> {
         self.errors.clone()
     }
 }
 
-impl<T> std::fmt::Display for ThreadGroup<T> {
+impl<T> std::fmt::Display for ScopedThreadGroup<'_, '_, T> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}::ThreadGroup {}", module_path!(), &self.id)
+        write!(f, "{}::ScopedThreadGroup {}", module_path!(), &self.id)
     }
 }
 
-impl<T: Send + Sync + 'static> Default for ThreadGroup<T> {
-    fn default() -> ThreadGroup<T> {
-        Self::new()
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub enum Error {
     ThreadGroupError(String),
-    ThreadJoinError(String),
-    ThreadSpawnError(String),
+    ThreadJoinError { thread: String, index: usize, message: Option<String> },
+    ThreadSpawnError { name: String, source: Arc<std::io::Error> },
+    Timeout { group: String, running: Vec<String> },
+    Aborted { cause: Box<Error>, running: Vec<String> },
+    TaskError { thread: String, index: usize, source: Arc<dyn std::error::Error + Send + Sync> },
 }
 
 impl Display for Error {
@@ -144,9 +2698,26 @@ impl Display for Error {
             "{}{}",
             self.prefix().unwrap_or_default(),
             match self {
-                Self::ThreadGroupError(s)
-                | Self::ThreadJoinError(s)
-                | Self::ThreadSpawnError(s) => format!("{}", s),
+                Self::ThreadGroupError(s) => s.to_string(),
+                Self::ThreadJoinError { thread, message, .. } => format!(
+                    "joining thread {}: {}",
+                    thread,
+                    message.as_deref().unwrap_or("panicked")
+                ),
+                Self::ThreadSpawnError { name, source } => {
+                    format!("spawning thread {}: {}", name, source)
+                },
+                Self::Timeout { group, running } => format!(
+                    "threads still running in group {}: {}",
+                    group,
+                    running.join(", ")
+                ),
+                Self::Aborted { cause, running } => {
+                    format!("{}; threads still running: {}", cause, running.join(", "))
+                },
+                Self::TaskError { thread, source, .. } => {
+                    format!("task in thread {} failed: {}", thread, source)
+                },
             }
         )
     }
@@ -156,19 +2727,57 @@ impl Error {
     pub fn variant(&self) -> String {
         match self {
             Error::ThreadGroupError(_) => "ThreadGroupError",
-            Error::ThreadJoinError(_) => "ThreadJoinError",
-            Error::ThreadSpawnError(_) => "ThreadSpawnError",
+            Error::ThreadJoinError { .. } => "ThreadJoinError",
+            Error::ThreadSpawnError { .. } => "ThreadSpawnError",
+            Error::Timeout { .. } => "Timeout",
+            Error::Aborted { .. } => "Aborted",
+            Error::TaskError { .. } => "TaskError",
         }
         .to_string()
     }
 
     fn prefix(&self) -> Option<String> {
-        match self {
-            _ => Some(format!("{}: ", self.variant())),
+        Some(format!("{}: ", self.variant()))
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        match (self, other) {
+            (Error::ThreadGroupError(a), Error::ThreadGroupError(b)) => a == b,
+            (
+                Error::ThreadJoinError { thread, index, message },
+                Error::ThreadJoinError { thread: t, index: i, message: m },
+            ) => thread == t && index == i && message == m,
+            (
+                Error::ThreadSpawnError { name, source },
+                Error::ThreadSpawnError { name: n, source: s },
+            ) => name == n && source.kind() == s.kind() && source.to_string() == s.to_string(),
+            (Error::Timeout { group, running }, Error::Timeout { group: g, running: r }) => {
+                group == g && running == r
+            },
+            (Error::Aborted { cause, running }, Error::Aborted { cause: c, running: r }) => {
+                cause == c && running == r
+            },
+            (
+                Error::TaskError { thread, index, source },
+                Error::TaskError { thread: t, index: i, source: s },
+            ) => thread == t && index == i && source.to_string() == s.to_string(),
+            _ => false,
         }
     }
 }
+impl Eq for Error {}
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ThreadSpawnError { source, .. } => Some(source.as_ref()),
+            Error::Aborted { cause, .. } => Some(cause.as_ref()),
+            Error::TaskError { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 pub type Result<T> = std::result::Result<T, Error>;